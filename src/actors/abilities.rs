@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    entities::{Interactable, Map},
+    to_ivec2,
+    ui::LogEvent,
+};
+
+use super::{
+    CharacterClass, CurrentPlayer, Sampler, Stats, Team, Velocity,
+    actions::{Claimed, ClaimedBy, InHands, Kicked},
+    experience::{Experience, TACKLE_XP},
+    physics::{BallPhysics, TILE_SIZE, calculate_kick_velocity},
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<ClassAbility>()
+        .register_type::<Cooldown>()
+        .register_type::<Threaded>()
+        .add_observer(tick_cooldown)
+        .add_observer(pay_for_ability)
+        .add_observer(slide_tackle)
+        .add_observer(play_ball)
+        .add_observer(organize);
+}
+
+/// How far a slide tackle reaches, in tiles
+const SLIDE_RANGE: i32 = 2;
+/// How much more likely a slide tackle wins the ball than a regular tackle
+const SLIDE_BONUS: f32 = 2.0;
+/// How far the ball rolls after a successful slide tackle, in tiles
+const LOOSE_BALL_DISTANCE: f32 = 2.0;
+/// How much harder a power shot is hit than a regular kick
+const POWER_SHOT_BONUS: f32 = 1.5;
+/// How far the shout of a keeper carries, in tiles
+const ORGANIZE_RANGE: f32 = 20.0;
+/// AP every teammate in range gains from the shout of a keeper
+const ORGANIZE_AP: usize = 2;
+/// How much of their defense opponents can use against a through ball
+pub const THREADED_BLOCK_FACTOR: f32 = 0.5;
+
+/// Special move of every class, used with K
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ClassAbility {
+    /// Defenders slide into a ball carrier up to two tiles away
+    SlideTackle,
+    /// Midfielders and wingers thread a pass that opponents struggle to intercept
+    ThroughBall,
+    /// Attackers hit the ball a lot harder than a regular kick
+    PowerShot,
+    /// Keepers organize the players around them, who gain AP
+    Organize,
+}
+
+impl ClassAbility {
+    pub fn for_class(class: &CharacterClass) -> Self {
+        match class {
+            CharacterClass::Goalkeeper => ClassAbility::Organize,
+            CharacterClass::CentralDefender => ClassAbility::SlideTackle,
+            CharacterClass::Midfielder | CharacterClass::Winger => ClassAbility::ThroughBall,
+            CharacterClass::Attacker => ClassAbility::PowerShot,
+        }
+    }
+
+    pub fn ap_cost(&self) -> usize {
+        match self {
+            ClassAbility::SlideTackle => 4,
+            ClassAbility::ThroughBall => 3,
+            ClassAbility::PowerShot => 5,
+            ClassAbility::Organize => 2,
+        }
+    }
+
+    /// Turns of the user until the ability can be used again
+    pub fn cooldown(&self) -> usize {
+        match self {
+            ClassAbility::SlideTackle => 3,
+            ClassAbility::ThroughBall => 2,
+            ClassAbility::PowerShot => 3,
+            ClassAbility::Organize => 4,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClassAbility::SlideTackle => "slide tackle",
+            ClassAbility::ThroughBall => "through ball",
+            ClassAbility::PowerShot => "power shot",
+            ClassAbility::Organize => "organize",
+        }
+    }
+
+    /// Returns true if the ability can be aimed at `target` by a player standing on `position`
+    pub fn can_target(&self, position: IVec2, target: IVec2, has_ball: bool, map: &Map) -> bool {
+        match self {
+            ClassAbility::SlideTackle => {
+                // the ball follows its carrier, so only a carrier shares their tile with it
+                let has_carrier = map.get(&target).is_some_and(|entities| {
+                    entities.iter().any(|(_, i)| i == &Interactable::Person)
+                        && entities.iter().any(|(_, i)| i == &Interactable::Ball)
+                });
+                let in_range = (target - position).abs().max_element() <= SLIDE_RANGE;
+                !has_ball && target != position && in_range && has_carrier
+            }
+            ClassAbility::ThroughBall | ClassAbility::PowerShot => has_ball && target != position,
+            ClassAbility::Organize => true,
+        }
+    }
+}
+
+/// Turns left until a player can use their class ability again
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Cooldown(pub usize);
+
+impl Cooldown {
+    pub fn is_ready(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Marks a through ball, opponents have a harder time stopping it
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Threaded;
+
+/// Triggered on a player that uses their class ability on the target position
+#[derive(Event)]
+pub struct UseAbility {
+    pub ability: ClassAbility,
+    pub target: Vec3,
+}
+
+fn tick_cooldown(trigger: Trigger<OnAdd, CurrentPlayer>, mut query: Query<&mut Cooldown>) {
+    if let Ok(mut cooldown) = query.get_mut(trigger.entity()) {
+        cooldown.0 = cooldown.0.saturating_sub(1);
+    }
+}
+
+fn pay_for_ability(
+    trigger: Trigger<UseAbility>,
+    mut query: Query<(&mut Stats, &Name)>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let ability = trigger.event().ability;
+    let Ok((mut stats, name)) = query.get_mut(trigger.entity()) else {
+        return;
+    };
+    stats.ap = stats.ap.saturating_sub(stats.ability_cost(ability));
+    commands
+        .entity(trigger.entity())
+        .insert(Cooldown(ability.cooldown()));
+    events.send(LogEvent(format!("{} uses {}", name, ability.label())));
+}
+
+type Slider<'a> = (
+    &'a Transform,
+    &'a mut Stats,
+    &'a Team,
+    &'a Name,
+    Option<&'a Claimed>,
+    Has<InHands>,
+);
+
+fn slide_tackle(
+    trigger: Trigger<UseAbility>,
+    mut sampler: ResMut<Sampler>,
+    physics: Res<BallPhysics>,
+    map: Res<Map>,
+    mut players: Query<Slider>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let UseAbility {
+        ability: ClassAbility::SlideTackle,
+        target,
+    } = *trigger.event()
+    else {
+        return;
+    };
+    let entity = trigger.entity();
+    let carrier = map.get(&to_ivec2(target)).and_then(|entities| {
+        entities.iter().map(|(entity, _)| *entity).find(|entity| {
+            players
+                .get(*entity)
+                .is_ok_and(|(.., claimed, _)| claimed.is_some())
+        })
+    });
+    let Some(carrier) = carrier else {
+        return;
+    };
+    let Ok([slider, carried]) = players.get_many_mut([entity, carrier]) else {
+        return;
+    };
+    let (transform, mut stats, team, name, _, _) = slider;
+    let (carrier_transform, carrier_stats, carrier_team, carrier_name, claimed, in_hands) = carried;
+    if team == carrier_team || in_hands {
+        return;
+    }
+
+    let chance = (stats.tackle_chance(&carrier_stats) * SLIDE_BONUS).min(1.0);
+    if sampler.0.random_range(0.0..=1.0) < chance {
+        // the ball keeps going in the direction of the slide
+        let direction = (carrier_transform.translation - transform.translation)
+            .truncate()
+            .normalize_or_zero();
+        commands.entity(carrier).remove::<Claimed>();
+        if let Some(claimed) = claimed {
+            commands
+                .entity(claimed.0)
+                .remove::<ClaimedBy>()
+                .insert(Kicked(
+                    direction * physics.speed_for_distance(LOOSE_BALL_DISTANCE),
+                ));
+        }
+        commands.trigger_targets(Experience(TACKLE_XP), entity);
+        events.send(LogEvent(format!(
+            "{} slides in and knocks the ball away from {}",
+            name, carrier_name
+        )));
+    } else {
+        stats.ap = 0;
+        events.send(LogEvent(format!(
+            "{} slides past {} and ends up on the ground",
+            name, carrier_name
+        )));
+    }
+}
+
+/// Through balls and power shots, both release the ball towards the target
+fn play_ball(
+    trigger: Trigger<UseAbility>,
+    physics: Res<BallPhysics>,
+    mut players: Query<(&Transform, &mut Stats, &Velocity, &Claimed)>,
+    mut commands: Commands,
+) {
+    let UseAbility { ability, target } = *trigger.event();
+    let entity = trigger.entity();
+    let Ok((transform, mut stats, velocity, claimed)) = players.get_mut(entity) else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    match ability {
+        ClassAbility::ThroughBall => {
+            let velocity = calculate_kick_velocity(
+                stats.pass_range(),
+                position,
+                target.truncate(),
+                velocity.0,
+                &physics,
+            );
+            commands
+                .entity(claimed.0)
+                .insert((Kicked(velocity), Threaded));
+        }
+        ClassAbility::PowerShot => {
+            let direction = (target.truncate() - position).normalize_or_zero();
+            commands
+                .entity(claimed.0)
+                .insert(Kicked(direction * stats.kick_strength() * POWER_SHOT_BONUS));
+        }
+        _ => return,
+    }
+    stats.tire_from_kick();
+    commands.entity(claimed.0).remove::<ClaimedBy>();
+    commands.entity(entity).remove::<(Claimed, InHands)>();
+}
+
+fn organize(
+    trigger: Trigger<UseAbility>,
+    mut players: Query<(Entity, &Transform, &Team, &mut Stats)>,
+) {
+    if trigger.event().ability != ClassAbility::Organize {
+        return;
+    }
+    let entity = trigger.entity();
+    let Ok((_, transform, team, _)) = players.get(entity) else {
+        return;
+    };
+    let (position, team) = (transform.translation, *team);
+    for (other, transform, other_team, mut stats) in &mut players {
+        let distance = transform.translation.distance(position) / TILE_SIZE;
+        if other != entity && *other_team == team && distance <= ORGANIZE_RANGE {
+            stats.ap += ORGANIZE_AP;
+        }
+    }
+}
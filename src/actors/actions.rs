@@ -9,21 +9,36 @@ use crate::{
     AppSet, PostUpdateSet,
     actors::Team,
     entities::{Interactable, Map},
+    relics::Modifiers,
+    run::GoalScored,
     states::*,
     to_ivec2,
     ui::LogEvent,
 };
 
 use super::{
-    CurrentPlayer, PointerObject, PreviewPath, Sampler, Stats, Velocity,
-    pathfinding::{self, CalculatedPath},
+    Ball, CharacterClass, Curl, CurrentPlayer, PassPreview, PointerObject, PreviewPath, Sampler,
+    Stats, Velocity,
+    abilities::{ClassAbility, Cooldown, THREADED_BLOCK_FACTOR, Threaded, UseAbility},
+    aerial::{AerialBall, HeaderChance, HeaderKind},
+    experience::{Experience, LastTouch, SAVE_XP, Touch},
+    injuries::Hit,
+    pathfinding::{CalculatedPath, PathQuery},
+    physics::{
+        Airborne, BallEvent, BallPhysics, BallState, Loft, PLAYER_REACH, Spin, TILE_SIZE,
+        calculate_kick_velocity, calculate_loft, calculate_shot_velocity, calculate_spin,
+        compensate_curl, step_ball,
+    },
+    reactions::{MIN_OVERWATCH_AP, Overwatch, Watcher},
+    substitutions::{Booking, Substitute},
 };
 
 pub fn plugin(app: &mut App) {
     app.register_type::<CurrentActions>()
         .register_type::<Kicked>()
+        .register_type::<InHands>()
         .insert_resource(CurrentActions { actions: vec![] })
-        .insert_resource(PreviewPath { path: vec![] })
+        .insert_resource(PreviewPath::default())
         .add_plugins(InputManagerPlugin::<Slots>::default())
         .add_plugins(InputManagerPlugin::<PlayerAbilities>::default())
         .add_systems(
@@ -32,7 +47,13 @@ pub fn plugin(app: &mut App) {
         )
         .add_systems(
             Update,
-            (report_abilities_used, process_actions, process_control).in_set(AppSet::Update),
+            (
+                report_abilities_used,
+                process_actions,
+                process_control,
+                put_down_outside_box,
+            )
+                .in_set(AppSet::Update),
         )
         .add_systems(FixedUpdate, process_kick)
         .add_systems(
@@ -40,12 +61,27 @@ pub fn plugin(app: &mut App) {
             (
                 calculate_ui_actions,
                 calculate_current_actions.run_if(in_state(GameplayStates::PlayerTurn)),
+                offer_header
+                    .after(calculate_ui_actions)
+                    .after(calculate_current_actions)
+                    .run_if(in_state(GameplayStates::PlayerTurn)),
             )
                 .in_set(PostUpdateSet::Move)
                 .run_if(in_state(AppState::Gameplay)),
         );
 }
 
+/// Kick strength at which a ball kicked at a player hits with a force of 1.0
+const KICK_INJURY_STRENGTH: f32 = 20.0;
+/// Force of a foul for the injury roll
+const FOUL_FORCE: f32 = 1.0;
+/// How far a keeper can throw the ball, in tiles
+pub const THROW_RANGE: f32 = 20.0;
+/// How far a goal kick flies per point of kick strength, in tiles
+const GOAL_KICK_RANGE: f32 = 3.0;
+/// How much speed a parried shot keeps
+const PARRY_DAMPING: f32 = 0.4;
+
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct CurrentActions {
@@ -58,19 +94,45 @@ pub enum PossibleAction {
     Header(Entity),
     EntityCommands(Vec<(String, String, bool)>),
     Command(String, String, bool),
+    /// An opponent and their chance to intercept the previewed pass
+    Interception(Entity, f32),
 }
 
-fn calculate_ui_actions(
+/// Keys our players choose a header with
+const HEADER_SLOTS: [(Slots, &str, HeaderKind); 3] = [
+    (Slots::Ability4, "j", HeaderKind::Shot),
+    (Slots::Ability5, "k", HeaderKind::Clear),
+    (Slots::Ability6, "l", HeaderKind::KnockDown),
+];
+
+/// Our player who won a ball in the air and still has to head it
+type PendingHeader<'a> = (&'a Transform, &'a Team, &'a Stats);
+
+/// The current player, the ball they control and whether they hold it in their hands
+type CurrentCarrier<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Stats,
+    &'a CharacterClass,
+    Option<&'a Cooldown>,
+    Option<&'a Claimed>,
+    Has<InHands>,
+);
+
+pub(super) fn calculate_ui_actions(
     map: Res<Map>,
+    curl: Res<Curl>,
     path: Res<PreviewPath>,
+    pass_preview: Res<PassPreview>,
     pointer: Single<&Transform, With<PointerObject>>,
-    current_player: Single<(Entity, &Stats, Option<&Claimed>), With<CurrentPlayer>>,
+    current_player: Single<CurrentCarrier, With<CurrentPlayer>>,
     mut commands: Commands,
 ) {
     let transform = pointer.into_inner();
     let target_position = to_ivec2(transform.translation);
     let mut actions = vec![];
-    let (current_entity, stats, claimed_option) = current_player.into_inner();
+    let (current_entity, current_transform, stats, class, cooldown, claimed_option, in_hands) =
+        current_player.into_inner();
     if claimed_option.is_some() {
         actions.push(PossibleAction::Command(
             "j".to_string(),
@@ -88,7 +150,7 @@ fn calculate_ui_actions(
             actions.push(PossibleAction::StatBlock(current_entity));
         }
     }
-    let in_range = path.path.len() <= stats.ap + 1;
+    let in_range = path.in_range(stats.ap);
     actions.push(PossibleAction::Command(
         "f".to_string(),
         "walk".to_string(),
@@ -127,7 +189,67 @@ fn calculate_ui_actions(
             "pass".to_string(),
             true,
         ));
+        actions.push(PossibleAction::Command(
+            "n".to_string(),
+            "lob".to_string(),
+            true,
+        ));
+        actions.push(PossibleAction::Command(
+            "m".to_string(),
+            "chip".to_string(),
+            true,
+        ));
+        for (entity, _, chance) in &pass_preview.interceptions {
+            actions.push(PossibleAction::Interception(*entity, *chance));
+        }
     }
+    // curl bends passes as well as shots
+    let direction = match curl.0.signum() {
+        1 => "left",
+        -1 => "right",
+        _ => "none",
+    };
+    actions.push(PossibleAction::Command(
+        "q/e".to_string(),
+        format!("curl: {} {}", direction, curl.0.abs()),
+        true,
+    ));
+    if in_hands {
+        actions.push(PossibleAction::Command(
+            "k".to_string(),
+            "throw".to_string(),
+            true,
+        ));
+        actions.push(PossibleAction::Command(
+            "l".to_string(),
+            "goal kick".to_string(),
+            true,
+        ));
+    } else {
+        let ability = ClassAbility::for_class(class);
+        let description = match cooldown.filter(|cooldown| !cooldown.is_ready()) {
+            Some(cooldown) => format!("{} (ready in {} turns)", ability.label(), cooldown.0),
+            None => format!("{} ({} AP)", ability.label(), stats.ability_cost(ability)),
+        };
+        actions.push(PossibleAction::Command(
+            "k".to_string(),
+            description,
+            ability_available(
+                ability,
+                current_transform,
+                stats,
+                cooldown,
+                claimed_option,
+                target_position,
+                &map,
+            ),
+        ));
+    }
+    actions.push(PossibleAction::Command(
+        "r".to_string(),
+        format!("overwatch ({} AP)", stats.ap),
+        stats.ap >= MIN_OVERWATCH_AP,
+    ));
     actions.push(PossibleAction::Command(
         "SPACE".to_string(),
         "skip".to_string(),
@@ -136,22 +258,28 @@ fn calculate_ui_actions(
     commands.insert_resource(CurrentActions { actions });
 }
 
-fn calculate_current_actions(
+pub(super) fn calculate_current_actions(
     map: Res<Map>,
     path: Res<PreviewPath>,
     pointer: Single<&Transform, With<PointerObject>>,
     ability_slot: Single<&mut AbilitySlotMap>,
-    current_player: Single<(Entity, &Stats, Option<&Claimed>), With<CurrentPlayer>>,
+    current_player: Single<CurrentCarrier, With<CurrentPlayer>>,
 ) {
     let mut slot_map = ability_slot.into_inner();
     slot_map.clear();
 
     let transform = pointer.into_inner();
     let target_position = to_ivec2(transform.translation);
-    let (current_entity, stats, claimed_option) = current_player.into_inner();
-    let in_range = path.path.len() <= stats.ap + 1;
+    let (current_entity, current_transform, stats, class, cooldown, claimed_option, in_hands) =
+        current_player.into_inner();
+    let in_range = path.in_range(stats.ap);
 
     slot_map.insert(Slots::Ability0, PlayerAbilities::Skip);
+    slot_map.insert(Slots::Ability11, PlayerAbilities::CurlLeft);
+    slot_map.insert(Slots::Ability12, PlayerAbilities::CurlRight);
+    if stats.ap >= MIN_OVERWATCH_AP {
+        slot_map.insert(Slots::Ability9, PlayerAbilities::Overwatch);
+    }
     if in_range {
         slot_map.insert(Slots::Ability1, PlayerAbilities::Walk);
     }
@@ -173,12 +301,78 @@ fn calculate_current_actions(
             }
         }
     }
-    if claimed_option.is_some() {
-        slot_map.insert(
-            Slots::Ability4,
-            PlayerAbilities::Pass(claimed_option.unwrap().0),
-        );
+    if let Some(claimed) = claimed_option {
+        slot_map.insert(Slots::Ability4, PlayerAbilities::Pass(claimed.0));
+        slot_map.insert(Slots::Ability7, PlayerAbilities::Lob(claimed.0));
+        slot_map.insert(Slots::Ability8, PlayerAbilities::Chip(claimed.0));
+        if in_hands {
+            slot_map.insert(Slots::Ability5, PlayerAbilities::Throw(claimed.0));
+            slot_map.insert(Slots::Ability6, PlayerAbilities::GoalKick(claimed.0));
+        }
+    }
+    let ability = ClassAbility::for_class(class);
+    // keepers holding the ball distribute it with K and L instead
+    if !in_hands
+        && ability_available(
+            ability,
+            current_transform,
+            stats,
+            cooldown,
+            claimed_option,
+            target_position,
+            &map,
+        )
+    {
+        slot_map.insert(Slots::Ability5, PlayerAbilities::Special(ability));
+    }
+}
+
+/// Replaces every other action with the choice of a header while one of our players has a ball in the air to play
+fn offer_header(
+    map: Res<Map>,
+    ability_slot: Single<&mut AbilitySlotMap>,
+    headers: Query<PendingHeader, With<HeaderChance>>,
+    mut commands: Commands,
+) {
+    let Some((transform, team, stats)) = headers.iter().find(|(_, team, _)| **team == Team::Player)
+    else {
+        return;
+    };
+    let mut slot_map = ability_slot.into_inner();
+    slot_map.clear();
+    let mut actions = vec![];
+    for (slot, key, kind) in HEADER_SLOTS {
+        let available = kind.available(transform.translation.truncate(), *team, stats.ap, &map);
+        if available {
+            slot_map.insert(slot, PlayerAbilities::Header(kind));
+        }
+        actions.push(PossibleAction::Command(
+            key.to_string(),
+            kind.label().to_string(),
+            available,
+        ));
     }
+    commands.insert_resource(CurrentActions { actions });
+}
+
+/// Returns true if the class ability is off cooldown, affordable and can be aimed at the target
+fn ability_available(
+    ability: ClassAbility,
+    transform: &Transform,
+    stats: &Stats,
+    cooldown: Option<&Cooldown>,
+    claimed: Option<&Claimed>,
+    target: IVec2,
+    map: &Map,
+) -> bool {
+    cooldown.is_none_or(Cooldown::is_ready)
+        && stats.ap >= stats.ability_cost(ability)
+        && ability.can_target(
+            to_ivec2(transform.translation),
+            target,
+            claimed.is_some(),
+            map,
+        )
 }
 
 #[derive(Actionlike, Reflect, Clone, Hash, Eq, PartialEq, Debug, Copy)]
@@ -188,7 +382,23 @@ pub enum PlayerAbilities {
     Kick(Entity),
     Foul(Entity),
     Pass(Entity),
+    Lob(Entity),
+    Chip(Entity),
+    Throw(Entity),
+    GoalKick(Entity),
+    /// The class ability of the current player
+    Special(ClassAbility),
+    /// Ends the turn and keeps the remaining AP for a reaction
+    Overwatch,
+    /// Replaces the current player with someone from the bench
+    Substitute,
     Skip,
+    /// What the player who won a ball in the air does with it
+    Header(HeaderKind),
+    /// Curls the next pass or shot further to the left
+    CurlLeft,
+    /// Curls the next pass or shot further to the right
+    CurlRight,
 }
 
 #[derive(Actionlike, Reflect, Clone, Hash, Eq, PartialEq, Debug, Copy)]
@@ -200,13 +410,20 @@ pub enum Slots {
     Ability4,
     Ability5,
     Ability6,
+    Ability7,
+    Ability8,
+    Ability9,
+    Ability10,
+    Ability11,
+    Ability12,
 }
 
 impl Slots {
     fn variants() -> impl Iterator<Item = Slots> {
         use Slots::*;
         [
-            Ability0, Ability1, Ability2, Ability3, Ability4, Ability5, Ability6,
+            Ability0, Ability1, Ability2, Ability3, Ability4, Ability5, Ability6, Ability7,
+            Ability8, Ability9, Ability10, Ability11, Ability12,
         ]
         .iter()
         .copied()
@@ -229,10 +446,17 @@ pub fn setup_slotmap(mut commands: Commands) {
             (Slots::Ability4, KeyCode::KeyJ),
             (Slots::Ability5, KeyCode::KeyK),
             (Slots::Ability6, KeyCode::KeyL),
+            (Slots::Ability7, KeyCode::KeyN),
+            (Slots::Ability8, KeyCode::KeyM),
+            (Slots::Ability9, KeyCode::KeyR),
+            (Slots::Ability10, KeyCode::KeyU),
+            (Slots::Ability11, KeyCode::KeyQ),
+            (Slots::Ability12, KeyCode::KeyE),
         ]),
         ActionState::<Slots>::default(),
         ActionState::<PlayerAbilities>::default(),
         AbilitySlotMap::default(),
+        StateScoped(AppState::Gameplay),
     ));
 }
 
@@ -261,20 +485,38 @@ pub struct ActionQueue(pub Vec<Action>);
 
 #[derive(Reflect)]
 pub enum Action {
+    /// Walks towards the target as far as the AP allow
     MoveTo(Vec3),
-    Kick(Entity),
+    /// Walks to the target only if it can be reached with the remaining AP
+    Approach(Vec3),
+    /// Which entity is kicked and how much it curls
+    Kick(Entity, i8),
     TakeControl(Entity),
     Foul(Entity),
-    /// Which entity has to be passed where
-    Pass(Entity, Vec3),
+    /// Which entity has to be passed where and how much it curls
+    Pass(Entity, Vec3, i8),
+    /// Which entity has to be lifted where and how much it curls
+    Loft(Entity, Vec3, Loft, i8),
+    /// Which entity the keeper throws where
+    Throw(Entity, Vec3),
+    /// Which entity the keeper kicks out of their hands where
+    GoalKick(Entity, Vec3),
+    /// Which class ability is used on which position
+    UseAbility(ClassAbility, Vec3),
+    /// Reserves the remaining AP to react during the opponents turn
+    Overwatch,
+    /// Brings on a substitute for the current player
+    Substitute,
     DefendGoal,
     SkipTurn,
     EndTurn(Team),
     Advance,
-    PassDown,
+    /// Kicks the ball back down the field
+    PassDown(Entity),
 }
 
 fn report_abilities_used(
+    curl: Res<Curl>,
     query: Query<&ActionState<PlayerAbilities>>,
     player: Option<Single<&mut ActionQueue, With<CurrentPlayer>>>,
     target: Option<Single<&Transform, With<PointerObject>>>,
@@ -291,26 +533,68 @@ fn report_abilities_used(
                     queue.0.push(Action::MoveTo(target_transform.translation));
                 }
                 PlayerAbilities::Kick(target) => {
-                    queue.0.push(Action::Kick(target));
-                    queue.0.push(Action::MoveTo(target_transform.translation));
+                    queue.0.push(Action::Kick(target, curl.0));
+                    queue.0.push(Action::Approach(target_transform.translation));
                 }
                 PlayerAbilities::TakeControl(target) => {
                     queue.0.push(Action::TakeControl(target));
-                    queue.0.push(Action::MoveTo(target_transform.translation));
+                    queue.0.push(Action::Approach(target_transform.translation));
                 }
                 PlayerAbilities::Foul(target) => {
                     queue.0.push(Action::Foul(target));
-                    queue.0.push(Action::MoveTo(target_transform.translation));
+                    queue.0.push(Action::Approach(target_transform.translation));
                 }
                 PlayerAbilities::Pass(target) => {
                     queue
                         .0
-                        .push(Action::Pass(target, target_transform.translation));
+                        .push(Action::Pass(target, target_transform.translation, curl.0));
+                }
+                PlayerAbilities::Lob(target) => {
+                    queue.0.push(Action::Loft(
+                        target,
+                        target_transform.translation,
+                        Loft::Lob,
+                        curl.0,
+                    ));
+                }
+                PlayerAbilities::Chip(target) => {
+                    queue.0.push(Action::Loft(
+                        target,
+                        target_transform.translation,
+                        Loft::Chip,
+                        curl.0,
+                    ));
+                }
+                PlayerAbilities::Throw(target) => {
+                    queue
+                        .0
+                        .push(Action::Throw(target, target_transform.translation));
+                }
+                PlayerAbilities::GoalKick(target) => {
+                    queue
+                        .0
+                        .push(Action::GoalKick(target, target_transform.translation));
+                }
+                PlayerAbilities::Special(ability) => {
+                    queue
+                        .0
+                        .push(Action::UseAbility(ability, target_transform.translation));
+                }
+                PlayerAbilities::Overwatch => {
+                    queue.0.push(Action::EndTurn(Team::Enemy));
+                    queue.0.push(Action::Overwatch);
+                }
+                PlayerAbilities::Substitute => {
+                    queue.0.push(Action::Substitute);
                 }
                 PlayerAbilities::Skip => {
                     queue.0.push(Action::EndTurn(Team::Enemy));
                     queue.0.push(Action::SkipTurn);
                 }
+                // curl is kept until the next pass or shot and headers are played by whoever won the ball
+                PlayerAbilities::CurlLeft
+                | PlayerAbilities::CurlRight
+                | PlayerAbilities::Header(_) => (),
             }
         }
     }
@@ -318,8 +602,9 @@ fn report_abilities_used(
 
 fn process_actions(
     mut sampler: ResMut<Sampler>,
-    time: Res<Time<Fixed>>,
+    physics: Res<BallPhysics>,
     map: Res<Map>,
+    modifiers: Res<Modifiers>,
     mut query: Query<(
         Entity,
         &Name,
@@ -330,7 +615,8 @@ fn process_actions(
         &Team,
     )>,
     interactables: Query<(Entity, &Transform, &Interactable, &Name)>,
-    stat_query: Query<&Stats>,
+    mut stat_query: Query<&mut Stats>,
+    claims: Query<(&Claimed, Has<InHands>)>,
     mut events: EventWriter<LogEvent>,
     mut next: ResMut<NextState<GameplayStates>>,
     mut commands: Commands,
@@ -343,43 +629,104 @@ fn process_actions(
         if let Some(action) = queue.0.pop() {
             match action {
                 Action::MoveTo(target) => {
-                    let Ok(path) = pathfinding::calculate_path(transform.translation, target, &map)
-                    else {
-                        events.send(LogEvent(format!("{} can't find a way to the target", name)));
-                        continue;
+                    let stats = stat_query.get(entity).unwrap();
+                    let path = match PathQuery::new(transform.translation, target)
+                        .with_team(modifiers.zone_team(stats, *team))
+                        .with_surcharge(stats.step_surcharge(claims.contains(entity)))
+                        .run(&map)
+                    {
+                        Ok(path) => path,
+                        Err(error) => {
+                            events.send(LogEvent(format!("{} can't move there, {}", name, error)));
+                            continue;
+                        }
+                    };
+                    commands
+                        .entity(entity)
+                        .insert((Velocity(Vec2::ZERO), CalculatedPath::new(path, 0.25)));
+                }
+                Action::Approach(target) => {
+                    let stats = stat_query.get(entity).unwrap();
+                    let path = match PathQuery::new(transform.translation, target)
+                        .with_team(modifiers.zone_team(stats, *team))
+                        .with_surcharge(stats.step_surcharge(claims.contains(entity)))
+                        .with_max_cost(stats.ap)
+                        .run(&map)
+                    {
+                        Ok(path) => path,
+                        Err(error) => {
+                            // the follow up action only makes sense at the target
+                            queue.0.pop();
+                            events.send(LogEvent(format!("{} can't get there, {}", name, error)));
+                            continue;
+                        }
                     };
                     commands
                         .entity(entity)
                         .insert((Velocity(Vec2::ZERO), CalculatedPath::new(path, 0.25)));
                 }
-                Action::Kick(target) => {
+                Action::Kick(target, curl) => {
                     let (_, _, interactable, target_name) = interactables.get(target).unwrap();
                     let article = match interactable {
                         Interactable::Ball => "the",
                         Interactable::Person => {
                             let stat = stat_query.get(target).unwrap();
                             let random = sampler.0.random_range(0.0..=1.0);
-                            if random < stat.defense {
+                            if random < stat.block_chance() {
                                 events.send(LogEvent(format!(
                                     "{} tried to kicked {}, but {} evaded",
                                     name, target_name, target_name
                                 )));
                                 continue;
                             }
+                            let force = stat_query.get(entity).unwrap().kick_strength()
+                                / KICK_INJURY_STRENGTH;
+                            commands.trigger_targets(Hit { force }, target);
                             ""
                         }
                         _ => "",
                     };
+                    if interactable == &Interactable::Ball {
+                        commands.trigger_targets(Touch(entity), target);
+                    }
                     let stats = stat_query.get(entity).unwrap();
-                    commands
-                        .entity(target)
-                        .insert(Kicked(velocity.0 * stats.kick_strength));
+                    let shot = calculate_shot_velocity(stats.kick_strength(), velocity.0);
+                    if interactable == &Interactable::Ball {
+                        // a curled shot bends around whoever stands in the way
+                        let spin = calculate_spin(
+                            curl,
+                            stats.passing_skill,
+                            sampler.0.random_range(-1.0..=1.0),
+                        );
+                        commands.entity(target).insert((Kicked(shot), spin));
+                    } else {
+                        commands.entity(target).insert(Kicked(shot));
+                    }
+                    stat_query.get_mut(entity).unwrap().tire_from_kick();
                     events.send(LogEvent(format!(
                         "{} kicked {}{}",
                         name, article, target_name
                     )));
                 }
                 Action::TakeControl(target) => {
+                    if claims
+                        .iter()
+                        .any(|(claimed, in_hands)| in_hands && claimed.0 == target)
+                    {
+                        events.send(LogEvent(format!(
+                            "{} can't take the ball out of the keeper's hands",
+                            name
+                        )));
+                        continue;
+                    }
+                    // with two balls on the field nobody can keep both
+                    if let Some((claimed, _)) = claims
+                        .get(entity)
+                        .ok()
+                        .filter(|(claimed, _)| claimed.0 != target)
+                    {
+                        commands.entity(claimed.0).remove::<ClaimedBy>();
+                    }
                     commands.entity(entity).insert(Claimed(target));
                     commands.entity(target).insert(ClaimedBy(entity));
                     let (_, _, interactable, target_name) = interactables.get(target).unwrap();
@@ -393,23 +740,117 @@ fn process_actions(
                         name, article, target_name
                     )));
                 }
-                Action::Foul(target) => {}
-                Action::Pass(target, target_position) => {
+                Action::Foul(target) => {
+                    let (_, _, _, target_name) = interactables.get(target).unwrap();
+                    events.send(LogEvent(format!("{} fouls {}", name, target_name)));
+                    commands.trigger_targets(Hit { force: FOUL_FORCE }, target);
+                    commands.trigger_targets(Booking, entity);
+                }
+                Action::Pass(target, target_position, curl) => {
                     let stats = stat_query.get(entity).unwrap();
-                    let velocity = calculate_kick_velocity(
+                    let spin = calculate_spin(
+                        curl,
                         stats.passing_skill,
+                        sampler.0.random_range(-1.0..=1.0),
+                    );
+                    let velocity = calculate_kick_velocity(
+                        stats.pass_range(),
                         transform.translation.truncate(),
                         target_position.truncate(),
-                        time.delta_secs(),
                         velocity.0,
+                        &physics,
+                    );
+                    // the pass is aimed for the intended curl, the skill decides how close the actual one comes
+                    let state = compensate_curl(
+                        BallState::rolling(transform.translation.truncate(), velocity),
+                        calculate_spin(curl, stats.passing_skill, 0.0),
+                        &physics,
                     );
                     commands
                         .entity(target)
-                        .insert(Kicked(velocity))
+                        .insert((Kicked(state.velocity), spin))
                         .remove::<ClaimedBy>();
-                    commands.entity(entity).remove::<Claimed>();
+                    commands.entity(entity).remove::<(Claimed, InHands)>();
+                    stat_query.get_mut(entity).unwrap().tire_from_kick();
                     events.send(LogEvent(format!("{} is passing the ball", name)));
                 }
+                Action::Loft(target, target_position, loft, curl) => {
+                    let stats = stat_query.get(entity).unwrap();
+                    let spin = calculate_spin(
+                        curl,
+                        stats.passing_skill,
+                        sampler.0.random_range(-1.0..=1.0),
+                    );
+                    let state = compensate_curl(
+                        calculate_loft(
+                            stats.pass_range(),
+                            transform.translation.truncate(),
+                            target_position.truncate(),
+                            loft,
+                            &physics,
+                        ),
+                        calculate_spin(curl, stats.passing_skill, 0.0),
+                        &physics,
+                    );
+                    commands
+                        .entity(target)
+                        .insert((Kicked(state.velocity), state.airborne, spin))
+                        .remove::<ClaimedBy>();
+                    commands.entity(entity).remove::<(Claimed, InHands)>();
+                    let verb = match loft {
+                        Loft::Lob => "lobbing",
+                        Loft::Chip => "chipping",
+                    };
+                    stat_query.get_mut(entity).unwrap().tire_from_kick();
+                    events.send(LogEvent(format!("{} is {} the ball", name, verb)));
+                }
+                Action::Throw(target, target_position) => {
+                    // throws are accurate, but can't go far
+                    let velocity = calculate_kick_velocity(
+                        THROW_RANGE,
+                        transform.translation.truncate(),
+                        target_position.truncate(),
+                        Vec2::ZERO,
+                        &physics,
+                    );
+                    commands
+                        .entity(target)
+                        .insert(Kicked(velocity))
+                        .remove::<ClaimedBy>();
+                    commands.entity(entity).remove::<(Claimed, InHands)>();
+                    events.send(LogEvent(format!("{} throws the ball", name)));
+                }
+                Action::GoalKick(target, target_position) => {
+                    let stats = stat_query.get(entity).unwrap();
+                    let state = calculate_loft(
+                        stats.kick_strength() * GOAL_KICK_RANGE,
+                        transform.translation.truncate(),
+                        target_position.truncate(),
+                        Loft::Chip,
+                        &physics,
+                    );
+                    commands
+                        .entity(target)
+                        .insert((Kicked(state.velocity), state.airborne))
+                        .remove::<ClaimedBy>();
+                    commands.entity(entity).remove::<(Claimed, InHands)>();
+                    stat_query.get_mut(entity).unwrap().tire_from_kick();
+                    events.send(LogEvent(format!(
+                        "{} kicks the ball out of their hands",
+                        name
+                    )));
+                }
+                Action::UseAbility(ability, target) => {
+                    commands.trigger_targets(UseAbility { ability, target }, entity);
+                }
+                Action::Overwatch => {
+                    let stats = stat_query.get(entity).unwrap();
+                    commands.entity(entity).insert(Overwatch { ap: stats.ap });
+                    events.send(LogEvent(format!("{} keeps {} AP to react", name, stats.ap)));
+                }
+                Action::Substitute => {
+                    commands.trigger_targets(Substitute, entity);
+                }
                 Action::DefendGoal => {
                     let (sum, count) = interactables
                         .iter()
@@ -420,11 +861,12 @@ fn process_actions(
                         .fold((Vec3::ZERO, 0), |(acc, count), v| (acc + v, count + 1));
 
                     let midpoint = sum / count as f32;
+                    // the keeper watches whichever ball is closest to the goal
                     let ball = interactables
                         .iter()
                         .filter(|(_, _, interactable, _)| *interactable == &Interactable::Ball)
                         .map(|(_, transform, _, _)| transform.translation)
-                        .next()
+                        .min_by(|a, b| a.distance(midpoint).total_cmp(&b.distance(midpoint)))
                         .unwrap();
                     let direction = (ball - midpoint).normalize_or_zero();
                     // radius of goal is 7 tiles times 8.0 pixel per tiles
@@ -459,7 +901,7 @@ fn process_actions(
                         .0
                         .push(Action::MoveTo(closest.translation + Vec3::X * 8.0));
                 }
-                Action::PassDown => {
+                Action::PassDown(target) => {
                     let closest = interactables
                         .iter()
                         .filter(|(_, _, interactable, _)| {
@@ -474,150 +916,268 @@ fn process_actions(
                                 .unwrap_or(std::cmp::Ordering::Equal)
                         })
                         .unwrap();
-                    let target = interactables
-                        .iter()
-                        .filter(|(_, _, interactable, _)| *interactable == &Interactable::Ball)
-                        .map(|(entity, _, _, _)| entity)
-                        .next()
-                        .unwrap();
-                    queue.0.push(Action::Pass(target, closest.translation));
+                    queue.0.push(Action::Pass(target, closest.translation, 0));
                 }
             }
         }
     }
 }
 
-pub fn calculate_kick_velocity(
-    pass_distance: f32,
-    ball_position: Vec2,
-    target_position: Vec2,
-    time: f32,
-    velocity: Vec2,
-) -> Vec2 {
-    let diff = target_position - ball_position;
-    let direction = diff.normalize_or_zero();
-    let actual_distance = diff.length() / 8.0;
-
-    let speed = (pass_distance / time).min(actual_distance / time);
-    let kick_velocity = direction * speed + velocity * 0.5;
-    kick_velocity
-}
+type Flight<'a> = (
+    Option<&'a Airborne>,
+    Option<&'a Spin>,
+    Has<Threaded>,
+    Has<Ball>,
+    Option<&'a LastTouch>,
+);
 
-fn process_kick(
+pub fn process_kick(
     mut sampler: ResMut<Sampler>,
-    time: Res<Time>,
+    physics: Res<BallPhysics>,
     map: Res<Map>,
     current_player: Option<Single<Entity, With<CurrentPlayer>>>,
     mut query: Query<(&Name, &mut Transform, &mut Kicked, Entity)>,
+    ball_states: Query<Flight>,
     interactables: Query<(&Name, &Stats), With<Interactable>>,
+    keepers: Query<(Entity, &Transform, &Team, &CharacterClass), Without<Kicked>>,
+    watchers: Query<Watcher, Without<Kicked>>,
     mut commands: Commands,
     mut events: EventWriter<LogEvent>,
+    mut aerial_balls: EventWriter<AerialBall>,
 ) {
-    const EPSILON: f32 = 0.1;
     let current_entity = if let Some(current) = current_player {
         current.into_inner()
     } else {
         Entity::PLACEHOLDER
     };
+    let current_team = keepers
+        .get(current_entity)
+        .ok()
+        .map(|(_, _, team, _)| *team);
+    // watchers whose reservation is used up, but not removed yet
+    let mut spent = vec![];
     for (name, mut transform, mut kicked, entity) in &mut query {
-        let mut translation = transform.translation;
-        let total_movement = kicked.0 * time.delta_secs();
-        let steps = total_movement.length().ceil() as i32;
-        let step_size = total_movement.extend(0.0) / steps as f32;
-
-        let mut exit = false;
-        for _ in 0..steps {
-            let next_translation = translation + step_size;
-            let current_position = to_ivec2(translation);
-            let next_position = to_ivec2(next_translation);
-            if let Some(entities) = map.get(&next_position) {
-                for (next_entity, interactable) in entities {
-                    if *next_entity == current_entity && *next_entity == entity {
-                        continue;
+        let (airborne_option, spin_option, threaded, is_ball, last_touch) =
+            ball_states.get(entity).unwrap();
+        // stopping the ball only counts as a save when an opponent played it
+        let last_team = last_touch
+            .and_then(|last| keepers.get(last.player).ok())
+            .map(|(_, _, team, _)| *team);
+        let is_save = |keeper_team: Team| last_team.is_some_and(|team| team != keeper_team);
+        let block_factor = if threaded { THREADED_BLOCK_FACTOR } else { 1.0 };
+        let mut state = BallState {
+            position: transform.translation.truncate(),
+            velocity: kicked.0,
+            airborne: airborne_option.copied().unwrap_or_default(),
+            spin: spin_option.copied().unwrap_or_default(),
+        };
+        let ball_events = step_ball(
+            &mut state,
+            &physics,
+            &map,
+            &[current_entity, entity],
+            &mut |blocker| {
+                let (_, stats) = interactables.get(blocker).unwrap();
+                sampler.0.random_range(0.0..=1.0) < stats.block_chance() * block_factor
+            },
+        );
+        // caught balls and goals are taken care of by the keeper or the kickoff
+        let mut stopped = false;
+        for ball_event in ball_events {
+            match ball_event {
+                BallEvent::Blocked(blocker, _) => {
+                    let (player, _) = interactables.get(blocker).unwrap();
+                    // keepers may catch the ball in their own penalty box
+                    let keeper_team = keepers
+                        .get(blocker)
+                        .ok()
+                        .filter(|(_, keeper, team, class)| {
+                            is_ball
+                                && matches!(class, CharacterClass::Goalkeeper)
+                                && map.in_penalty_box(to_ivec2(keeper.translation), **team)
+                        })
+                        .map(|(_, _, team, _)| *team);
+                    if let Some(keeper_team) = keeper_team {
+                        catch_ball(&mut commands, blocker, entity);
+                        if is_save(keeper_team) {
+                            commands.trigger_targets(Experience(SAVE_XP), blocker);
+                        }
+                        stopped = true;
+                        events.send(LogEvent(format!("{} catches the {}", player, name)));
+                    } else {
+                        events.send(LogEvent(format!("{} blocked incoming {}", player, name)));
                     }
-                    match interactable {
-                        &Interactable::Wall => {
-                            let normal = get_wall_normal(current_position, &map);
-                            kicked.0 = reflect_velocity(kicked.0, normal);
-                            exit = true;
+                }
+                BallEvent::Header(player, at) => {
+                    aerial_balls.send(AerialBall {
+                        ball: entity,
+                        player,
+                        at,
+                    });
+                }
+                // a kicked player that ends up in the net is no goal
+                BallEvent::Goal(..) if !is_ball => (),
+                BallEvent::Goal(team, at) => {
+                    let keeper = keepers
+                        .iter()
+                        .filter(|(_, transform, keeper_team, class)| {
+                            matches!(class, CharacterClass::Goalkeeper)
+                                && **keeper_team == team
+                                && map.in_penalty_box(to_ivec2(transform.translation), team)
+                        })
+                        .map(|(keeper, transform, _, _)| {
+                            (
+                                keeper,
+                                transform.translation.truncate().distance(at) / TILE_SIZE,
+                            )
+                        })
+                        .min_by(|(_, a), (_, b)| {
+                            a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
+                        });
+                    let Some((keeper, distance)) = keeper else {
+                        commands.trigger(GoalScored {
+                            by: team.opponent(),
+                            ball: entity,
+                        });
+                        stopped = true;
+                        continue;
+                    };
+                    let (keeper_name, stats) = interactables.get(keeper).unwrap();
+                    let chance = stats.dive_chance(distance);
+                    let roll = sampler.0.random_range(0.0..=1.0);
+                    if roll >= chance {
+                        if chance > 0.0 {
+                            events.send(LogEvent(format!(
+                                "{} dives, but can't reach the {}",
+                                keeper_name, name
+                            )));
                         }
-                        &Interactable::Person => {
-                            let (player, stats) = interactables.get(*next_entity).unwrap();
-                            let random = sampler.0.random_range(0.0..=1.0);
-                            if random < stats.defense {
-                                kicked.0 = Vec2::ZERO;
-                                exit = true;
-                                events.send(LogEvent(format!(
-                                    "{} blocked incoming {}",
-                                    player, name
-                                )));
-                            }
+                        commands.trigger(GoalScored {
+                            by: team.opponent(),
+                            ball: entity,
+                        });
+                        stopped = true;
+                    } else if roll < chance / 2.0 {
+                        catch_ball(&mut commands, keeper, entity);
+                        if is_save(team) {
+                            commands.trigger_targets(Experience(SAVE_XP), keeper);
                         }
-                        &Interactable::Goal(team) => {
-                            let normal = get_wall_normal(current_position, &map);
-                            let is_goal = match team {
-                                Team::Enemy => normal.x < 0.0,
-                                Team::Player => normal.x > 0.0,
-                            };
-                            kicked.0 = if is_goal {
-                                // count goal
-                                // reset ball?
-                                Vec2::ZERO
-                            } else {
-                                reflect_velocity(kicked.0, normal)
-                            };
-                            exit = true;
+                        stopped = true;
+                        events.send(LogEvent(format!(
+                            "{} dives and holds on to the {}",
+                            keeper_name, name
+                        )));
+                    } else {
+                        if is_save(team) {
+                            commands.trigger_targets(Experience(SAVE_XP), keeper);
                         }
-                        _ => (),
+                        // the ball bounces back into the field
+                        state.velocity =
+                            Vec2::new(-state.velocity.x, state.velocity.y) * PARRY_DAMPING;
+                        events.send(LogEvent(format!(
+                            "{} dives and parries the {}",
+                            keeper_name, name
+                        )));
                     }
                 }
+                _ => (),
             }
-            if exit {
+        }
+        // watching opponents of the kicking team step into passes that roll through their area
+        let low_pass = is_ball && !stopped && state.airborne.height < PLAYER_REACH;
+        if let Some(kicking_team) = current_team.filter(|_| low_pass) {
+            for (watcher, watcher_transform, overwatch, stats, team, watcher_name) in &watchers {
+                if *team == kicking_team
+                    || spent.contains(&watcher)
+                    || !overwatch.covers(watcher_transform.translation, state.position.extend(0.0))
+                {
+                    continue;
+                }
+                spent.push(watcher);
+                commands.entity(watcher).remove::<Overwatch>();
+                if !overwatch.succeeds(stats.defense(), &mut sampler.0) {
+                    events.send(LogEvent(format!(
+                        "{} jumps into the passing lane, but misses",
+                        watcher_name
+                    )));
+                    continue;
+                }
+                state.position = watcher_transform.translation.truncate();
+                commands.entity(watcher).insert(Claimed(entity));
+                commands
+                    .entity(entity)
+                    .insert(ClaimedBy(watcher))
+                    .remove::<(Kicked, Spin, Airborne, Threaded)>();
+                events.send(LogEvent(format!(
+                    "{} reads the pass and intercepts it",
+                    watcher_name
+                )));
+                stopped = true;
                 break;
             }
-            translation = next_translation;
         }
-        transform.translation = translation;
-        kicked.0 *= 0.9;
-        if kicked.0.length() < EPSILON {
-            commands.entity(entity).remove::<Kicked>();
+        transform.translation = state.position.extend(transform.translation.z);
+        if stopped {
+            continue;
+        }
+        kicked.0 = state.velocity;
+        if state.is_airborne() {
+            commands.entity(entity).insert(state.airborne);
+        } else if airborne_option.is_some() {
+            commands.entity(entity).remove::<Airborne>();
+        }
+        if state.is_resting(&physics) {
+            commands.entity(entity).remove::<(Kicked, Spin, Threaded)>();
+        } else if spin_option.is_some() {
+            commands.entity(entity).insert(state.spin);
         }
     }
 }
 
+/// Velocity of a rolling ball or player in tiles per second
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Kicked(pub Vec2);
 
-fn reflect_velocity(velocity: Vec2, normal: Vec2) -> Vec2 {
-    velocity - 2.0 * velocity.dot(normal) * normal
-}
-
-fn get_wall_normal(position: IVec2, map: &Map) -> Vec2 {
-    let mut normal = Vec2::ZERO;
-    if map.contains_key(&(position + IVec2::X)) {
-        normal += Vec2::NEG_X;
-    }
-    if map.contains_key(&(position - IVec2::X)) {
-        normal += Vec2::X;
-    }
-    if map.contains_key(&(position + IVec2::Y)) {
-        normal += Vec2::NEG_Y;
-    }
-    if map.contains_key(&(position - IVec2::Y)) {
-        normal += Vec2::Y;
-    }
-
-    normal.normalize_or_zero()
-}
-
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct ClaimedBy(pub Entity);
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-pub struct Claimed(Entity);
+pub struct Claimed(pub Entity);
+
+/// Marks a keeper that holds the ball in their hands, nobody can take it from them
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct InHands;
+
+/// Puts the ball into the hands of a keeper
+pub fn catch_ball(commands: &mut Commands, keeper: Entity, ball: Entity) {
+    commands.entity(keeper).insert((Claimed(ball), InHands));
+    commands
+        .entity(ball)
+        .insert(ClaimedBy(keeper))
+        .remove::<(Kicked, Spin, Airborne, Threaded)>();
+}
+
+/// Keepers may only hold the ball in their hands inside their own penalty box
+fn put_down_outside_box(
+    map: Res<Map>,
+    query: Query<(Entity, &Transform, &Team, &Name), With<InHands>>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    for (entity, transform, team, name) in &query {
+        if !map.in_penalty_box(to_ivec2(transform.translation), *team) {
+            commands.entity(entity).remove::<InHands>();
+            events.send(LogEvent(format!(
+                "{} has to put the ball down outside the box",
+                name
+            )));
+        }
+    }
+}
 
 fn process_control(
     mut query: Query<(&mut Transform, &ClaimedBy), Without<Claimed>>,
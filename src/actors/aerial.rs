@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use rand::Rng;
+
+use crate::{
+    AppSet,
+    entities::Map,
+    states::{AppState, GameplayStates},
+    to_ivec2, to_world,
+    ui::LogEvent,
+};
+
+use super::{
+    CharacterClass, Sampler, Stats, Team,
+    actions::{Kicked, PlayerAbilities, catch_ball, process_kick},
+    experience::{Experience, LastTouch, SAVE_XP, Touch},
+    physics::{BallPhysics, TILE_SIZE},
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<AerialBall>()
+        .register_type::<HeaderKind>()
+        .add_systems(FixedUpdate, resolve_aerial_balls.after(process_kick))
+        .add_systems(
+            Update,
+            resolve_headers
+                .in_set(AppSet::Update)
+                .run_if(in_state(AppState::Gameplay)),
+        );
+}
+
+/// Headers can go for goal closer than this to the opponents goal, in tiles
+const HEADER_SHOT_RANGE: f32 = 12.0;
+/// How far a defensive header clears the ball, in tiles
+const HEADER_CLEARANCE: f32 = 8.0;
+/// AP a header at goal or a clearance costs, knocking the ball down is free
+pub const HEADER_AP: usize = 1;
+
+/// What a player does with a ball they won in the air
+#[derive(Reflect, Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum HeaderKind {
+    Shot,
+    Clear,
+    /// Leaves the ball at the feet of the header
+    KnockDown,
+}
+
+impl HeaderKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeaderKind::Shot => "head at goal",
+            HeaderKind::Clear => "head clear",
+            HeaderKind::KnockDown => "knock down",
+        }
+    }
+
+    /// Returns true if a player of `team` with `ap` left can head the ball this way from `position`
+    pub fn available(&self, position: Vec2, team: Team, ap: usize, map: &Map) -> bool {
+        match self {
+            HeaderKind::Shot => {
+                ap >= HEADER_AP
+                    && goal_of(team.opponent(), map).is_some_and(|goal| {
+                        position.distance(goal) / TILE_SIZE <= HEADER_SHOT_RANGE
+                    })
+            }
+            HeaderKind::Clear => ap >= HEADER_AP,
+            HeaderKind::KnockDown => true,
+        }
+    }
+
+    /// Goes for goal when close enough, clears the ball in the own half and heads it down otherwise
+    fn pick(position: Vec2, team: Team, ap: usize, map: &Map) -> Self {
+        let in_own_half = match (goal_of(team, map), goal_of(team.opponent(), map)) {
+            (Some(own), Some(target)) => position.distance(own) < position.distance(target),
+            _ => false,
+        };
+        if HeaderKind::Shot.available(position, team, ap, map) {
+            HeaderKind::Shot
+        } else if in_own_half && HeaderKind::Clear.available(position, team, ap, map) {
+            HeaderKind::Clear
+        } else {
+            HeaderKind::KnockDown
+        }
+    }
+
+    /// Velocity of the ball after the header, `None` if it drops to the feet of the header
+    fn velocity(
+        &self,
+        position: Vec2,
+        team: Team,
+        map: &Map,
+        physics: &BallPhysics,
+    ) -> Option<Vec2> {
+        match self {
+            HeaderKind::Shot => {
+                let goal = goal_of(team.opponent(), map)?;
+                let distance = position.distance(goal) / TILE_SIZE;
+                // aim a little behind the goal line so the ball doesn't stop short
+                Some(
+                    (goal - position).normalize_or_zero()
+                        * physics.speed_for_distance(distance + 2.0),
+                )
+            }
+            HeaderKind::Clear => {
+                let goal = goal_of(team, map)?;
+                Some(
+                    (position - goal).normalize_or_zero()
+                        * physics.speed_for_distance(HEADER_CLEARANCE),
+                )
+            }
+            HeaderKind::KnockDown => None,
+        }
+    }
+}
+
+/// World position of the center of the goal `team` defends
+fn goal_of(team: Team, map: &Map) -> Option<Vec2> {
+    map.goals.get(&team).map(|goal| to_world(goal.center()))
+}
+
+/// Marks a player who won a ball in the air and still has to decide what to do with it
+#[derive(Component)]
+pub struct HeaderChance(pub Entity);
+
+/// Sent when a ball arrives at head height of a player
+#[derive(Event)]
+pub struct AerialBall {
+    pub ball: Entity,
+    pub player: Entity,
+    pub at: Vec2,
+}
+
+type Contender<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Stats,
+    &'a Team,
+    &'a CharacterClass,
+    &'a Name,
+);
+
+/// Everyone on or next to the tile of an aerial ball goes up for it.
+///
+/// The ball goes to the best roll, keepers use their hands inside their own penalty box.
+/// Whoever wins gets a [`HeaderChance`] to decide what to do with the ball.
+fn resolve_aerial_balls(
+    mut aerial_balls: EventReader<AerialBall>,
+    mut sampler: ResMut<Sampler>,
+    map: Res<Map>,
+    players: Query<Contender>,
+    touches: Query<&LastTouch>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    for AerialBall { ball, player, at } in aerial_balls.read() {
+        let tile = to_ivec2(at.extend(0.0));
+        let contenders = players
+            .iter()
+            .filter(|(entity, transform, ..)| {
+                *entity == *player
+                    || (to_ivec2(transform.translation) - tile).abs().max_element() <= 1
+            })
+            .collect::<Vec<_>>();
+        let is_duel = contenders
+            .iter()
+            .any(|(_, _, _, team, ..)| contenders.iter().any(|(_, _, _, other, ..)| team != other));
+
+        let in_hands = |(_, transform, _, team, class, _): &Contender| {
+            matches!(class, CharacterClass::Goalkeeper)
+                && map.in_penalty_box(to_ivec2(transform.translation), **team)
+        };
+        let winner = if is_duel {
+            contenders
+                .iter()
+                .map(|contender| {
+                    let (_, _, stats, ..) = contender;
+                    let skill = if in_hands(contender) {
+                        stats.defense()
+                    } else {
+                        stats.heading
+                    };
+                    (contender, sampler.0.random_range(0.0..=1.0) * skill)
+                })
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(contender, _)| *contender)
+        } else {
+            contenders
+                .iter()
+                .find(|(entity, ..)| entity == player)
+                .copied()
+        };
+        let Some(winner) = winner else {
+            continue;
+        };
+        let (entity, _, _, team, _, name) = winner;
+        if is_duel {
+            let losers = contenders
+                .iter()
+                .filter(|(_, _, _, other, ..)| other != &team)
+                .map(|(.., name)| name.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            events.send(LogEvent(format!(
+                "{} wins the ball in the air against {}",
+                name, losers
+            )));
+        }
+
+        if in_hands(&winner) {
+            catch_ball(&mut commands, entity, *ball);
+            // only crosses of the opponents count as saves
+            let crosser = touches
+                .get(*ball)
+                .ok()
+                .and_then(|last| players.get(last.player).ok());
+            if crosser.is_some_and(|(_, _, _, crosser_team, ..)| crosser_team != team) {
+                commands.trigger_targets(Experience(SAVE_XP), entity);
+            }
+            events.send(LogEvent(format!("{} catches the cross", name)));
+            continue;
+        }
+        commands.trigger_targets(Touch(entity), *ball);
+        commands.entity(entity).insert(HeaderChance(*ball));
+    }
+}
+
+type Header<'a> = (
+    Entity,
+    &'a HeaderChance,
+    &'a Transform,
+    &'a Team,
+    &'a mut Stats,
+    &'a Name,
+);
+
+/// Plays every header that has been decided.
+///
+/// Our players choose during our own turn, everyone else heads the ball the way [`HeaderKind::pick`] decides.
+fn resolve_headers(
+    state: Res<State<GameplayStates>>,
+    physics: Res<BallPhysics>,
+    map: Res<Map>,
+    abilities: Query<&ActionState<PlayerAbilities>>,
+    mut headers: Query<Header>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let chosen = abilities.iter().find_map(|ability_state| {
+        ability_state
+            .get_just_pressed()
+            .into_iter()
+            .find_map(|ability| match ability {
+                PlayerAbilities::Header(kind) => Some(kind),
+                _ => None,
+            })
+    });
+    for (entity, chance, transform, team, mut stats, name) in &mut headers {
+        let position = transform.translation.truncate();
+        let kind = if *state.get() == GameplayStates::PlayerTurn && *team == Team::Player {
+            match chosen {
+                Some(kind) if kind.available(position, *team, stats.ap, &map) => kind,
+                _ => continue,
+            }
+        } else {
+            HeaderKind::pick(position, *team, stats.ap, &map)
+        };
+        commands.entity(entity).remove::<HeaderChance>();
+        if kind != HeaderKind::KnockDown {
+            stats.ap -= HEADER_AP;
+        }
+        if let Some(velocity) = kind.velocity(position, *team, &map, &physics) {
+            commands.entity(chance.0).insert(Kicked(velocity));
+        }
+        let verb = match kind {
+            HeaderKind::Shot => "heads the ball towards goal",
+            HeaderKind::Clear => "heads the ball clear",
+            HeaderKind::KnockDown => "heads the ball down",
+        };
+        events.send(LogEvent(format!("{} {}", name, verb)));
+    }
+}
@@ -1,69 +1,183 @@
 use bevy::prelude::*;
 
-use crate::actors::actions::Action;
+use crate::{actors::actions::Action, entities::Map};
 
 use super::{
-    Ball, CharacterClass, CurrentPlayer, Team,
-    actions::{ActionQueue, Claimed, ClaimedBy},
+    Ball, CharacterClass, CurrentPlayer, Stats, Team, Velocity,
+    actions::{ActionQueue, Claimed, ClaimedBy, InHands, THROW_RANGE},
+    formations::KickoffSpot,
+    physics::{BallPhysics, BallState, TILE_SIZE, calculate_kick_velocity, simulate_ball},
+    tactics::{Instructions, LineHeight, Pressing, Role, Width},
 };
 
+/// How an opposing club plays, every one of their players carries it
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Component)]
+pub enum TacticalStyle {
+    /// Short passes to whoever the ball reaches best
+    Possession,
+    /// Passes and goal kicks go to the teammate furthest up the field
+    LongBall,
+    /// Sits deep and narrow, nobody chases us while we have the ball
+    ParkTheBus,
+}
+
+pub const STYLES: [TacticalStyle; 3] = [
+    TacticalStyle::Possession,
+    TacticalStyle::LongBall,
+    TacticalStyle::ParkTheBus,
+];
+
+impl TacticalStyle {
+    /// Team instructions the club plays with
+    pub fn instructions(&self) -> Instructions {
+        match self {
+            TacticalStyle::Possession => Instructions {
+                line: LineHeight::High,
+                pressing: Pressing::High,
+                width: Width::Wide,
+                counter_attack: false,
+            },
+            TacticalStyle::LongBall => Instructions {
+                line: LineHeight::Normal,
+                pressing: Pressing::Medium,
+                width: Width::Normal,
+                counter_attack: true,
+            },
+            TacticalStyle::ParkTheBus => Instructions {
+                line: LineHeight::Deep,
+                pressing: Pressing::Low,
+                width: Width::Narrow,
+                counter_attack: true,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for TacticalStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TacticalStyle::Possession => write!(f, "POSSESSION"),
+            TacticalStyle::LongBall => write!(f, "LONG BALL"),
+            TacticalStyle::ParkTheBus => write!(f, "PARK THE BUS"),
+        }
+    }
+}
+
+/// How far off-ball players drift from where their instructions want them before they walk back, in tiles
+const LEASH: f32 = 4.0;
+
+type Deciding<'a> = (
+    Entity,
+    Option<&'a Claimed>,
+    &'a CharacterClass,
+    &'a TacticalStyle,
+    &'a Instructions,
+    &'a Role,
+    &'a KickoffSpot,
+    &'a mut ActionQueue,
+);
+
 pub fn enemy_ai(
-    query: Single<(Option<&Claimed>, &CharacterClass, &mut ActionQueue), With<CurrentPlayer>>,
-    ball_query: Single<(Entity, &Transform, Option<&ClaimedBy>), With<Ball>>,
-    team_members: Query<(&Transform, &Team), Without<Ball>>,
+    map: Res<Map>,
+    physics: Res<BallPhysics>,
+    query: Single<Deciding, With<CurrentPlayer>>,
+    carriers: Query<(&Transform, &Stats, &Velocity)>,
+    balls: Query<(Entity, &Transform, Option<&ClaimedBy>), With<Ball>>,
+    team_members: Query<(Entity, &Transform, &Team, &CharacterClass), Without<Ball>>,
+    holding: Query<(), (With<CurrentPlayer>, With<InHands>)>,
 ) {
-    let (claim_option, class, mut action_queue) = query.into_inner();
-    if claim_option.is_some() {
+    let (current_entity, claim_option, class, style, instructions, role, spot, mut action_queue) =
+        query.into_inner();
+    if let Some(claim) = claim_option {
         match class {
-            &CharacterClass::Goalkeeper => {
-                action_queue.0.push(Action::PassDown);
-            }
-            &CharacterClass::CentralDefender => {
-                action_queue.0.push(Action::PassDown);
-            }
-            &CharacterClass::Midfielder => {
-                action_queue.0.push(Action::PassDown);
+            &CharacterClass::Goalkeeper
+            | &CharacterClass::CentralDefender
+            | &CharacterClass::Midfielder => {
+                let teammates = team_members
+                    .iter()
+                    .filter(|(entity, _, team, _)| {
+                        *entity != current_entity && **team == Team::Enemy
+                    })
+                    .map(|(entity, transform, ..)| (entity, transform.translation))
+                    .collect::<Vec<(Entity, Vec3)>>();
+                let ball = claim.0;
+                let carrier = carriers.get(current_entity).unwrap();
+                action_queue.0.push(Action::EndTurn(Team::Player));
+                let pass = best_pass(current_entity, carrier, &teammates, *style, &map, &physics);
+                if !holding.is_empty() {
+                    action_queue
+                        .0
+                        .push(distribute(carrier.0, pass, &teammates, *style, ball));
+                    return;
+                }
+                match pass {
+                    Some(target) => action_queue.0.push(Action::Pass(ball, target, 0)),
+                    None => action_queue.0.push(Action::PassDown(ball)),
+                }
             }
-            &CharacterClass::Attacker => {
+            &CharacterClass::Attacker | &CharacterClass::Winger => {
                 // when ball is claimed, advance
                 // when goal in range, take a shot
             }
         }
+    } else if *class == CharacterClass::Goalkeeper {
+        action_queue.0.push(Action::EndTurn(Team::Player));
+        action_queue.0.push(Action::DefendGoal);
     } else {
-        match class {
-            &CharacterClass::Goalkeeper => {
-                action_queue.0.push(Action::EndTurn(Team::Player));
-                action_queue.0.push(Action::DefendGoal);
+        let (transform, ..) = carriers.get(current_entity).unwrap();
+        // with two balls on the field players go after the closest one
+        let Some((ball, ball_transform, claimed_by_option)) = balls.iter().min_by(|a, b| {
+            let distance = |ball: &Transform| ball.translation.distance(transform.translation);
+            distance(a.1).total_cmp(&distance(b.1))
+        }) else {
+            action_queue.0.push(Action::EndTurn(Team::Player));
+            action_queue.0.push(Action::SkipTurn);
+            return;
+        };
+        let carrier = claimed_by_option
+            .and_then(|claimed_by| team_members.get(claimed_by.0).ok())
+            .map(|(_, transform, team, _)| (transform.translation, *team));
+        let forward = matches!(class, CharacterClass::Attacker | CharacterClass::Winger);
+        match carrier {
+            // when ball is claimed by a team member, advance
+            Some((_, Team::Enemy)) if forward => {
+                action_queue.0.push(Action::Advance);
             }
-            &CharacterClass::CentralDefender => {
-                // mark someone that comes into the defense field
-                // try to stay between that player and the ball in range
+            // when ball is claimed by an enemy, the closest players try to take it away
+            Some((carrier, Team::Player))
+                if presses(current_entity, carrier, instructions, &team_members) =>
+            {
+                action_queue.0.push(Action::EndTurn(Team::Player));
+                action_queue.0.push(Action::TakeControl(ball));
+                action_queue.0.push(Action::MoveTo(carrier));
             }
-            &CharacterClass::Midfielder => {
-                // mark a player
-                // try to stay between that player and the ball in range
+            // when ball is unclaimed, the forwards try to claim it
+            None if forward => {
+                action_queue.0.push(Action::EndTurn(Team::Player));
+                action_queue.0.push(Action::TakeControl(ball));
+                action_queue
+                    .0
+                    .push(Action::MoveTo(ball_transform.translation));
             }
-            &CharacterClass::Attacker => {
-                let (entity, transform, claimed_by_option) = ball_query.into_inner();
-                if let Some(claimed_by) = claimed_by_option {
-                    let (enemy, team) = team_members.get(claimed_by.0).unwrap();
-                    match team {
-                        // when ball is claimed by a team member, advance
-                        Team::Enemy => {
-                            action_queue.0.push(Action::Advance);
-                        }
-                        // when ball is claimed by an enemy, try to take it away
-                        Team::Player => {
-                            action_queue.0.push(Action::EndTurn(Team::Player));
-                            action_queue.0.push(Action::TakeControl(entity));
-                            action_queue.0.push(Action::MoveTo(enemy.translation));
-                        }
-                    }
-                } else {
-                    // when ball is unclaimed, try to claim it
-                    action_queue.0.push(Action::EndTurn(Team::Player));
-                    action_queue.0.push(Action::TakeControl(entity));
-                    action_queue.0.push(Action::MoveTo(transform.translation));
+            // everyone else holds the position their instructions give them
+            _ => {
+                action_queue.0.push(Action::EndTurn(Team::Player));
+                // only wait for passes and runners while the opponents have the ball
+                if matches!(carrier, Some((_, Team::Player))) {
+                    action_queue.0.push(Action::Overwatch);
+                }
+                let target = instructions.target(
+                    **spot,
+                    *role,
+                    Team::Enemy,
+                    carrier.map(|(_, team)| team),
+                    ball_transform.translation.truncate(),
+                );
+                if transform.translation.truncate().distance(target) > LEASH * TILE_SIZE {
+                    action_queue
+                        .0
+                        .push(Action::MoveTo(target.extend(transform.translation.z)));
                 }
             }
         }
@@ -73,3 +187,99 @@ pub fn enemy_ai(
         action_queue.0.push(Action::SkipTurn);
     }
 }
+
+/// Returns true if `player` is one of the outfield players of their team closest to the opposing ball carrier
+fn presses(
+    player: Entity,
+    carrier: Vec3,
+    instructions: &Instructions,
+    team_members: &Query<(Entity, &Transform, &Team, &CharacterClass), Without<Ball>>,
+) -> bool {
+    let mut pressers = team_members
+        .iter()
+        .filter(|(_, _, team, class)| {
+            **team == Team::Enemy && **class != CharacterClass::Goalkeeper
+        })
+        .map(|(entity, transform, ..)| (entity, transform.translation.distance(carrier)))
+        .collect::<Vec<_>>();
+    pressers.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    pressers
+        .iter()
+        .take(instructions.pressing.pressers())
+        .any(|(entity, _)| *entity == player)
+}
+
+/// How a keeper gets rid of the ball in their hands.
+///
+/// Close receivers get a throw, everyone else a goal kick, without a receiver the ball goes to the furthest teammate.
+/// Long ball teams never throw.
+fn distribute(
+    keeper: &Transform,
+    pass: Option<Vec3>,
+    teammates: &[(Entity, Vec3)],
+    style: TacticalStyle,
+    ball: Entity,
+) -> Action {
+    match pass {
+        Some(target)
+            if style != TacticalStyle::LongBall
+                && keeper.translation.distance(target) / TILE_SIZE <= THROW_RANGE =>
+        {
+            Action::Throw(ball, target)
+        }
+        Some(target) => Action::GoalKick(ball, target),
+        None => {
+            // enemies play towards the goal on the left
+            let target = teammates
+                .iter()
+                .map(|(_, position)| *position)
+                .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
+                .unwrap_or(keeper.translation);
+            Action::GoalKick(ball, target)
+        }
+    }
+}
+
+/// Picks the teammate further up the field that a pass reaches best, long ball teams pick the one furthest up.
+///
+/// Every candidate pass is simulated, a pass is good if the ball comes to rest close to the receiver.
+fn best_pass(
+    carrier_entity: Entity,
+    (transform, stats, velocity): (&Transform, &Stats, &Velocity),
+    teammates: &[(Entity, Vec3)],
+    style: TacticalStyle,
+    map: &Map,
+    physics: &BallPhysics,
+) -> Option<Vec3> {
+    // how far from the receiver a pass may come to rest, in tiles
+    const MAX_MISS: f32 = 2.0;
+    let position = transform.translation.truncate();
+    teammates
+        .iter()
+        // enemies play towards the goal on the left
+        .filter(|(_, target)| target.x < transform.translation.x)
+        .map(|(receiver, target)| {
+            let kick_velocity = calculate_kick_velocity(
+                stats.pass_range(),
+                position,
+                target.truncate(),
+                velocity.0,
+                physics,
+            );
+            let trajectory = simulate_ball(
+                BallState::rolling(position, kick_velocity),
+                physics,
+                map,
+                &[carrier_entity, *receiver],
+                |_| false,
+            );
+            let miss = trajectory.end().distance(target.truncate()) / TILE_SIZE;
+            (*target, miss)
+        })
+        .filter(|(_, miss)| *miss <= MAX_MISS)
+        .min_by(|(a_target, a), (b_target, b)| match style {
+            TacticalStyle::LongBall => a_target.x.total_cmp(&b_target.x),
+            _ => a.total_cmp(b),
+        })
+        .map(|(target, _)| target)
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::ui::LogEvent;
+
+use super::{Stats, Team, actions::ClaimedBy, traits::Trait};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<LastTouch>()
+        .register_type::<PassXp>()
+        .add_observer(gain_experience)
+        .add_observer(record_touch)
+        .add_observer(touch_on_claim);
+}
+
+pub const GOAL_XP: usize = 50;
+pub const ASSIST_XP: usize = 30;
+pub const SAVE_XP: usize = 20;
+pub const TACKLE_XP: usize = 15;
+pub const PASS_XP: usize = 5;
+/// Most experience a player earns from completed passes in one match
+const MAX_PASS_XP: usize = 50;
+
+/// What a player can spend a level up on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Improvement {
+    KickStrength,
+    Passing,
+    Defense,
+    Dribbling,
+    Heading,
+    Ap,
+    /// Lowers the AP cost of the class ability
+    Mastery,
+    /// Teaches the player a new trait
+    Learn(Trait),
+}
+
+impl Improvement {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Improvement::KickStrength => "kick strength",
+            Improvement::Passing => "passing",
+            Improvement::Defense => "defense",
+            Improvement::Dribbling => "dribbling",
+            Improvement::Heading => "heading",
+            Improvement::Ap => "AP",
+            Improvement::Mastery => "ability mastery",
+            Improvement::Learn(quirk) => quirk.label(),
+        }
+    }
+}
+
+/// Triggered on a player that earned experience
+#[derive(Event)]
+pub struct Experience(pub usize);
+
+/// Triggered on the ball whenever a player plays it
+#[derive(Event)]
+pub struct Touch(pub Entity);
+
+/// Who played the ball last and the teammate that gave it to them
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LastTouch {
+    pub player: Entity,
+    pub previous: Option<Entity>,
+}
+
+/// Experience a player earned from completed passes during the current match
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct PassXp(usize);
+
+fn gain_experience(
+    trigger: Trigger<Experience>,
+    mut players: Query<(&mut Stats, &Name)>,
+    mut events: EventWriter<LogEvent>,
+) {
+    let Ok((mut stats, name)) = players.get_mut(trigger.entity()) else {
+        return;
+    };
+    if stats.gain_xp(trigger.event().0) > 0 {
+        events.send(LogEvent(format!(
+            "{} reaches level {}",
+            name,
+            stats.level()
+        )));
+    }
+}
+
+/// A ball that reaches a teammate completes the pass of whoever played it before
+fn record_touch(
+    trigger: Trigger<Touch>,
+    balls: Query<&LastTouch>,
+    teams: Query<&Team>,
+    pass_xp: Query<&PassXp>,
+    mut commands: Commands,
+) {
+    let ball = trigger.entity();
+    let player = trigger.event().0;
+    let last = balls.get(ball).ok().map(|last| last.player);
+    if last == Some(player) {
+        return;
+    }
+    let passer = last.filter(|last| teams.get(*last).ok() == teams.get(player).ok());
+    if let Some(passer) = passer {
+        let earned = pass_xp.get(passer).map_or(0, |xp| xp.0);
+        if earned < MAX_PASS_XP {
+            commands.entity(passer).insert(PassXp(earned + PASS_XP));
+            commands.trigger_targets(Experience(PASS_XP), passer);
+        }
+    }
+    commands.entity(ball).insert(LastTouch {
+        player,
+        previous: passer,
+    });
+}
+
+fn touch_on_claim(
+    trigger: Trigger<OnAdd, ClaimedBy>,
+    balls: Query<&ClaimedBy>,
+    mut commands: Commands,
+) {
+    if let Ok(claimed_by) = balls.get(trigger.entity()) {
+        commands.trigger_targets(Touch(claimed_by.0), trigger.entity());
+    }
+}
@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+    scene::ron,
+};
+use serde::Deserialize;
+
+use crate::asset_tracking::LoadResource;
+
+use super::{CharacterClass, Team, physics::TILE_SIZE};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Formation>()
+        .register_type::<Formations>()
+        .register_type::<Anchor>()
+        .register_type::<KickoffSpot>()
+        .init_asset_loader::<FormationsLoader>()
+        .load_resource_from_path::<Formations>(Formations::PATH);
+}
+
+/// Players every team has on the field
+pub const FORMATION_SIZE: usize = 11;
+
+/// Where a player of a formation lines up, in tiles from the center spot for a team that plays towards the right
+#[derive(Reflect, Clone, Deserialize, Debug)]
+pub struct Anchor {
+    pub x: f32,
+    pub y: f32,
+    pub class: CharacterClass,
+}
+
+impl Anchor {
+    /// World position of the anchor, mirrored for the enemy
+    pub fn spot(&self, team: Team) -> Vec2 {
+        let side = match team {
+            Team::Player => 1.0,
+            Team::Enemy => -1.0,
+        };
+        Vec2::new(self.x * side, self.y) * TILE_SIZE
+    }
+}
+
+/// How a starting eleven lines up at kickoff
+#[derive(Reflect, Clone, Deserialize, Debug)]
+pub struct Formation {
+    pub name: Cow<'static, str>,
+    anchors: Vec<Anchor>,
+}
+
+impl Formation {
+    pub fn anchors(&self) -> &[Anchor] {
+        &self.anchors
+    }
+}
+
+/// Formations are told apart by their name
+impl PartialEq for Formation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl std::fmt::Display for Formation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Every formation in the data file, available once the loading screen is done
+#[derive(Resource, Asset, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct Formations(Vec<Formation>);
+
+impl Formations {
+    const PATH: &'static str = "data/formations.ron";
+
+    pub fn all(&self) -> &[Formation] {
+        &self.0
+    }
+
+    /// The formation every run starts with
+    pub fn first(&self) -> &Formation {
+        &self.0[0]
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|formation| formation.name == name)
+    }
+}
+
+#[derive(Debug)]
+enum FormationsError {
+    Io(std::io::Error),
+    Ron(ron::de::SpannedError),
+    /// None of the formations has eleven anchors
+    Empty,
+}
+
+impl std::fmt::Display for FormationsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormationsError::Io(error) => write!(f, "couldn't read the formations: {}", error),
+            FormationsError::Ron(error) => write!(f, "couldn't parse the formations: {}", error),
+            FormationsError::Empty => write!(f, "there is no valid formation"),
+        }
+    }
+}
+
+impl std::error::Error for FormationsError {}
+
+/// Reads the formations file, formations without eleven anchors are left out
+#[derive(Default)]
+struct FormationsLoader;
+
+impl AssetLoader for FormationsLoader {
+    type Asset = Formations;
+    type Settings = ();
+    type Error = FormationsError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Formations, FormationsError> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(FormationsError::Io)?;
+        let formations: Vec<Formation> =
+            ron::de::from_bytes(&bytes).map_err(FormationsError::Ron)?;
+        let formations: Vec<_> = formations
+            .into_iter()
+            .filter(|formation| {
+                let valid = formation.anchors.len() == FORMATION_SIZE;
+                if !valid {
+                    warn!(
+                        "Formation {} has {} anchors instead of {}",
+                        formation.name,
+                        formation.anchors.len(),
+                        FORMATION_SIZE
+                    );
+                }
+                valid
+            })
+            .collect();
+        if formations.is_empty() {
+            return Err(FormationsError::Empty);
+        }
+        Ok(Formations(formations))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// World position a player lines up on at kickoff and falls back to when they are out of position
+#[derive(Component, Reflect, Clone, Copy, Deref)]
+#[reflect(Component)]
+pub struct KickoffSpot(pub Vec2);
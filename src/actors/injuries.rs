@@ -0,0 +1,158 @@
+use bevy::{color::palettes::css::RED, prelude::*};
+use rand::Rng;
+
+use crate::{AppSet, GlyphAsset, states::AppState, ui::LogEvent};
+
+use super::{
+    Sampler, Stats,
+    substitutions::{Bench, LeaveField},
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Injury>()
+        .add_observer(roll_injury)
+        .add_systems(
+            Update,
+            show_injuries
+                .in_set(AppSet::Update)
+                .run_if(in_state(AppState::Gameplay)),
+        );
+}
+
+/// Chance of an injury from a hit with a force of 1.0
+const INJURY_CHANCE: f32 = 0.3;
+/// Most matches a serious injury keeps a player out of the run
+const MAX_MATCHES_OUT: usize = 3;
+
+/// How bad an injury is
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    /// Lowers the stats a little, the player can go on
+    Knock,
+    /// Lowers the stats a lot, the player can go on
+    Strain,
+    /// The player has to be substituted and misses later matches
+    Serious,
+}
+
+#[derive(Reflect, Clone, Copy, Debug)]
+pub struct Injury {
+    pub severity: Severity,
+    /// Matches of the run the player misses after this one
+    pub matches_out: usize,
+}
+
+impl Injury {
+    /// Share of their strength an injured player can still use
+    pub fn impairment(&self) -> f32 {
+        match self.severity {
+            Severity::Knock => 0.8,
+            Severity::Strain => 0.6,
+            Severity::Serious => 0.4,
+        }
+    }
+
+    pub fn forces_substitution(&self) -> bool {
+        self.severity == Severity::Serious
+    }
+}
+
+impl std::fmt::Display for Injury {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            Severity::Knock => write!(f, "knock"),
+            Severity::Strain => write!(f, "strain"),
+            Severity::Serious => write!(f, "serious, out for {} matches", self.matches_out),
+        }
+    }
+}
+
+/// Triggered on a player that got fouled or hit by the ball, `force` scales the injury chance
+#[derive(Event)]
+pub struct Hit {
+    pub force: f32,
+}
+
+fn roll_injury(
+    trigger: Trigger<Hit>,
+    mut sampler: ResMut<Sampler>,
+    mut players: Query<(&mut Stats, &Name)>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity();
+    let Ok((mut stats, name)) = players.get_mut(entity) else {
+        return;
+    };
+    let chance = INJURY_CHANCE * trigger.event().force * stats.fragility();
+    if sampler.0.random_range(0.0..=1.0) >= chance {
+        return;
+    }
+    let roll = sampler.0.random_range(0.0..=1.0);
+    let injury = if roll < 0.6 {
+        Injury {
+            severity: Severity::Knock,
+            matches_out: 0,
+        }
+    } else if roll < 0.9 {
+        Injury {
+            severity: Severity::Strain,
+            matches_out: 0,
+        }
+    } else {
+        Injury {
+            severity: Severity::Serious,
+            matches_out: sampler.0.random_range(1..=MAX_MATCHES_OUT),
+        }
+    };
+    let injury = stats.injure(injury);
+    events.send(LogEvent(format!("{} is injured ({})", name, injury)));
+    if injury.forces_substitution() {
+        commands.trigger_targets(LeaveField { replace: true }, entity);
+    }
+}
+
+/// Marks the status glyph above an injured player
+#[derive(Component)]
+struct InjuryGlyph;
+
+/// Players on the field whose stats changed
+type ChangedOnField = (Changed<Stats>, Without<Bench>);
+
+fn show_injuries(
+    glyphs: Res<GlyphAsset>,
+    players: Query<(Entity, &Stats, Option<&Children>), ChangedOnField>,
+    markers: Query<(), With<InjuryGlyph>>,
+    mut commands: Commands,
+) {
+    for (entity, stats, children) in &players {
+        let marker = children
+            .into_iter()
+            .flatten()
+            .find(|child| markers.contains(**child));
+        match (stats.injury.is_some(), marker) {
+            (true, None) => {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        Name::from("Injury"),
+                        Sprite {
+                            image: glyphs.glyph.clone_weak(),
+                            texture_atlas: Some(TextureAtlas {
+                                index: '+' as usize,
+                                layout: glyphs.atlas.clone_weak(),
+                            }),
+                            color: RED.into(),
+                            ..default()
+                        },
+                        Transform::from_xyz(4.0, 4.0, 0.5).with_scale(Vec3::splat(0.5)),
+                        InjuryGlyph,
+                    ));
+                });
+            }
+            (false, Some(marker)) => {
+                commands.entity(*marker).despawn_recursive();
+            }
+            _ => (),
+        }
+    }
+}
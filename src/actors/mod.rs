@@ -1,32 +1,61 @@
-use actions::{ActionQueue, Claimed, calculate_kick_velocity};
+use abilities::ClassAbility;
+use actions::{ActionQueue, Claimed};
 use bevy::{
-    color::palettes::css::{DARK_CYAN, GREEN, ORANGE, RED, WHITE, YELLOW},
+    color::palettes::css::{GREEN, ORANGE, RED, WHITE, YELLOW},
     prelude::*,
 };
+use experience::Improvement;
+use formations::KickoffSpot;
+use injuries::Injury;
 use leafwing_input_manager::prelude::*;
-use pathfinding::{CalculatedPath, calculate_path};
+use pathfinding::{CalculatedPath, PathQuery};
+use physics::{
+    BallEvent, BallPhysics, BallShadow, BallState, Loft, MAX_CURL, TILE_SIZE,
+    calculate_kick_velocity, calculate_loft, calculate_spin, compensate_curl, simulate_ball,
+};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use tactics::Role;
+use traits::{MAX_TRAITS, Trait};
 
 use crate::{
     AppSet, GlyphAsset,
     entities::{Interactable, Map},
+    relics::Modifiers,
+    run::Run,
     states::{AppState, GameplayStates},
     to_world,
 };
 
+pub mod abilities;
 pub mod actions;
+pub mod aerial;
 pub mod enemy;
+pub mod experience;
+pub mod formations;
+pub mod injuries;
 mod names;
-mod pathfinding;
+pub mod pathfinding;
+pub mod physics;
+mod reachability;
+pub mod reactions;
+pub mod substitutions;
+pub mod tactics;
+pub mod traits;
 
 pub fn plugin(app: &mut App) {
     app.register_type::<Stats>()
         .register_type::<Velocity>()
         .register_type::<CharacterClass>()
+        .register_type::<Kit>()
+        .register_type::<enemy::TacticalStyle>()
         .register_type::<ActionQueue>()
         .insert_resource(Sampler(ChaCha8Rng::from_os_rng()))
         .insert_resource(PointerIsDirty(true))
+        .init_resource::<PassPreview>()
+        .init_resource::<Curl>()
+        .register_type::<Curl>()
         .insert_gizmo_config(
             PassPreviewGizmos {},
             GizmoConfig {
@@ -35,24 +64,53 @@ pub fn plugin(app: &mut App) {
             },
         )
         .add_plugins(InputManagerPlugin::<PointerActions>::default())
-        .add_plugins((pathfinding::plugin, actions::plugin))
+        .add_plugins((
+            physics::plugin,
+            pathfinding::plugin,
+            reachability::plugin,
+            actions::plugin,
+            abilities::plugin,
+            aerial::plugin,
+            reactions::plugin,
+            injuries::plugin,
+            experience::plugin,
+            substitutions::plugin,
+            traits::plugin,
+            formations::plugin,
+            tactics::plugin,
+        ))
         .add_systems(
             Update,
             (
                 tick_pointer.in_set(AppSet::TickTimers),
+                update_curl
+                    .run_if(in_state(GameplayStates::PlayerTurn))
+                    .in_set(AppSet::RecordInput),
                 update_pointer
                     .run_if(in_state(AppState::Gameplay))
                     .in_set(AppSet::Update),
-                (preview_path.after(update_pointer), preview_pass)
+                (
+                    preview_path.after(update_pointer),
+                    update_pass_preview.after(update_pointer).run_if(is_dirty),
+                    preview_pass.after(update_pass_preview),
+                )
                     .run_if(in_state(GameplayStates::PlayerTurn))
                     .in_set(AppSet::Update),
             ),
         )
+        .add_systems(OnEnter(GameplayStates::PlayerTurn), reset_curl)
         .add_systems(Last, remove_dirty.run_if(is_dirty));
 }
 
 #[derive(Resource)]
-pub struct Sampler(ChaCha8Rng);
+pub struct Sampler(pub ChaCha8Rng);
+
+impl Sampler {
+    /// Makes every match of a run play out the same for the same seed
+    pub fn seeded(seed: u64) -> Self {
+        Sampler(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
 
 #[derive(Component)]
 pub struct PointerObject {
@@ -79,21 +137,67 @@ pub enum Team {
     Enemy,
 }
 
-#[derive(Component, Clone, Reflect)]
+impl Team {
+    pub fn opponent(&self) -> Team {
+        match self {
+            Team::Player => Team::Enemy,
+            Team::Enemy => Team::Player,
+        }
+    }
+}
+
+#[derive(Component, Clone, PartialEq, Eq, Reflect, Serialize, Deserialize, Debug)]
 #[reflect(Component)]
 pub enum CharacterClass {
     Goalkeeper,
     CentralDefender,
     Midfielder,
     Attacker,
+    /// Quick and wide, unlocked by the profile
+    Winger,
+}
+
+impl std::fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CharacterClass::Goalkeeper => write!(f, "GOALKEEPER"),
+            CharacterClass::CentralDefender => write!(f, "DEFENDER"),
+            CharacterClass::Midfielder => write!(f, "MIDFIELDER"),
+            CharacterClass::Attacker => write!(f, "ATTACKER"),
+            CharacterClass::Winger => write!(f, "WINGER"),
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct Ball;
 
-pub fn startup(mut sampler: ResMut<Sampler>, glyphs: Res<GlyphAsset>, mut commands: Commands) {
-    commands.spawn((
-        Name::from("Ball"),
+/// The extra ball of a double-ball match
+#[derive(Component)]
+pub struct SecondBall;
+
+/// Where the second ball of a double-ball match is put down at kickoff
+pub const SECOND_BALL_SPOT: Vec2 = Vec2::new(0.0, 8.0 * TILE_SIZE);
+
+/// Shirt colour of our team
+pub const HOME_KIT: Color = Color::Srgba(ORANGE);
+/// How much better the star of an opposing club is than their teammates
+const STAR_STRENGTH: f32 = 1.3;
+
+/// Colour a player wears while they aren't the current player
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct Kit(pub Color);
+
+/// Spawns a ball and its shadow
+fn spawn_ball<'a>(
+    commands: &'a mut Commands,
+    glyphs: &GlyphAsset,
+    name: &'static str,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    let mut ball = commands.spawn((
+        Name::from(name),
         Sprite {
             image: glyphs.glyph.clone_weak(),
             texture_atlas: Some(TextureAtlas {
@@ -102,69 +206,113 @@ pub fn startup(mut sampler: ResMut<Sampler>, glyphs: Res<GlyphAsset>, mut comman
             }),
             ..default()
         },
-        Transform::from_xyz(0.0, 0.0, 2.0),
+        Transform::from_translation(position.extend(2.0)),
         Interactable::Ball,
         Ball,
+        StateScoped(AppState::Gameplay),
     ));
-
-    let positions = [
-        (-45.0, 0.0, CharacterClass::Goalkeeper),
-        (-30.0, 8.0, CharacterClass::CentralDefender),
-        (-30.0, -8.0, CharacterClass::CentralDefender),
-        (-30.0, 24.0, CharacterClass::CentralDefender),
-        (-30.0, -24.0, CharacterClass::CentralDefender),
-        (-18.0, 0.0, CharacterClass::Midfielder),
-        (-15.0, 12.0, CharacterClass::Midfielder),
-        (-15.0, -12.0, CharacterClass::Midfielder),
-        (-5.0, 16.0, CharacterClass::Attacker),
-        (-5.0, -16.0, CharacterClass::Attacker),
-        (-4.0, 0.0, CharacterClass::Attacker),
-    ];
-
-    for (index, position) in positions.iter().enumerate() {
-        commands.spawn((
-            Name::from(random_name(&mut sampler.0)),
+    ball.with_children(|parent| {
+        parent.spawn((
+            Name::from("Ball Shadow"),
             Sprite {
                 image: glyphs.glyph.clone_weak(),
                 texture_atlas: Some(TextureAtlas {
-                    index: 1,
+                    index: 250,
                     layout: glyphs.atlas.clone_weak(),
                 }),
-                color: ORANGE.into(),
+                color: Color::BLACK.with_alpha(0.6),
                 ..default()
             },
-            Transform::from_xyz(position.0 * 8.0, position.1 * 8.0, 1.0),
-            Interactable::Person,
-            Stats::from_class(&position.2, index, &mut sampler.0),
-            ActionQueue::default(),
-            Velocity(Vec2::ZERO),
-            Team::Player,
-            position.2.clone(),
+            Transform::from_xyz(0.0, 0.0, -0.5),
+            Visibility::Hidden,
+            BallShadow,
         ));
+    });
+    ball
+}
+
+pub fn startup(
+    run: Res<Run>,
+    modifiers: Res<Modifiers>,
+    mut sampler: ResMut<Sampler>,
+    glyphs: Res<GlyphAsset>,
+    mut commands: Commands,
+) {
+    spawn_ball(&mut commands, &glyphs, "Ball", Vec2::ZERO);
+    if modifiers.second_ball {
+        spawn_ball(&mut commands, &glyphs, "Second Ball", SECOND_BALL_SPOT).insert(SecondBall);
     }
 
-    for (index, position) in positions.iter().enumerate() {
+    let starters = run.lineup().starters;
+    for (index, member) in starters {
+        let spot = run.formation.anchors()[index].spot(Team::Player);
+        let mut stats = member.stats.clone();
+        stats.initiative = index as u8;
         commands.spawn((
-            Name::from(random_name(&mut sampler.0)),
+            Name::from(member.name.clone()),
             Sprite {
                 image: glyphs.glyph.clone_weak(),
                 texture_atlas: Some(TextureAtlas {
                     index: 1,
                     layout: glyphs.atlas.clone_weak(),
                 }),
-                color: DARK_CYAN.into(),
+                color: HOME_KIT,
                 ..default()
             },
-            Transform::from_xyz(position.0 * -8.0, position.1 * 8.0, 1.0),
+            Transform::from_translation(spot.extend(1.0)),
+            KickoffSpot(spot),
             Interactable::Person,
-            Stats::from_class(&position.2, index, &mut sampler.0),
+            stats,
             ActionQueue::default(),
             Velocity(Vec2::ZERO),
-            Team::Enemy,
-            position.2.clone(),
+            Team::Player,
+            member.role,
+            run.instructions,
+            Kit(HOME_KIT),
+            member.class.clone(),
+            StateScoped(AppState::Gameplay),
         ));
     }
 
+    // the opponent lines up in their own formation, mirrored
+    if let Some(opponent) = run.opponent() {
+        for (index, anchor) in opponent.formation.anchors().iter().enumerate() {
+            let spot = anchor.spot(Team::Enemy);
+            let mut stats = Stats::from_class(&anchor.class, index, &mut sampler.0);
+            let mut name = random_name(&mut sampler.0);
+            if index == opponent.star_position {
+                stats.strengthen(STAR_STRENGTH);
+                name = opponent.star.clone();
+            }
+            stats.strengthen(opponent.strength);
+            commands.spawn((
+                Name::from(name),
+                Sprite {
+                    image: glyphs.glyph.clone_weak(),
+                    texture_atlas: Some(TextureAtlas {
+                        index: 1,
+                        layout: glyphs.atlas.clone_weak(),
+                    }),
+                    color: opponent.color,
+                    ..default()
+                },
+                Transform::from_translation(spot.extend(1.0)),
+                KickoffSpot(spot),
+                Interactable::Person,
+                stats,
+                ActionQueue::default(),
+                Velocity(Vec2::ZERO),
+                Team::Enemy,
+                Kit(opponent.color),
+                opponent.style,
+                opponent.style.instructions(),
+                Role::for_class(&anchor.class),
+                anchor.class.clone(),
+                StateScoped(AppState::Gameplay),
+            ));
+        }
+    }
+
     let input_map = InputMap::default()
         .with_dual_axis(PointerActions::Move, VirtualDPad::numpad())
         .with_dual_axis(PointerActions::Move, VirtualDPad::wasd())
@@ -178,6 +326,7 @@ pub fn startup(mut sampler: ResMut<Sampler>, glyphs: Res<GlyphAsset>, mut comman
             PointerObject {
                 timer: Timer::from_seconds(0.08, TimerMode::Repeating),
             },
+            StateScoped(AppState::Gameplay),
         ))
         .with_children(|parent| {
             parent.spawn((
@@ -196,7 +345,7 @@ pub fn startup(mut sampler: ResMut<Sampler>, glyphs: Res<GlyphAsset>, mut comman
     info!("done spawning");
 }
 
-fn random_name(sampler: &mut ChaCha8Rng) -> String {
+pub fn random_name(sampler: &mut ChaCha8Rng) -> String {
     let first_names = if sampler.random_bool(0.5) {
         names::FIRST_NAMES
     } else {
@@ -229,10 +378,53 @@ fn tick_pointer(time: Res<Time>, mut query: Query<&mut PointerObject>) {
     }
 }
 
-#[derive(Resource, Reflect)]
+/// How much the current player wants to curl their next pass, positive values curl to the left
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct Curl(pub i8);
+
+fn update_curl(
+    abilities: Query<&ActionState<actions::PlayerAbilities>>,
+    mut curl: ResMut<Curl>,
+    mut dirt: ResMut<PointerIsDirty>,
+) {
+    let pressed = |ability| {
+        abilities
+            .iter()
+            .any(|ability_state| ability_state.just_pressed(&ability))
+    };
+    let change = if pressed(actions::PlayerAbilities::CurlLeft) {
+        1
+    } else if pressed(actions::PlayerAbilities::CurlRight) {
+        -1
+    } else {
+        return;
+    };
+    curl.0 = (curl.0 + change).clamp(-MAX_CURL, MAX_CURL);
+    dirt.0 = true;
+}
+
+fn reset_curl(mut curl: ResMut<Curl>) {
+    curl.0 = 0;
+}
+
+#[derive(Resource, Reflect, Default)]
 #[reflect(Resource)]
 struct PreviewPath {
     path: Vec<IVec2>,
+    /// Accumulated cost to reach each tile of [`Self::path`]
+    costs: Vec<usize>,
+}
+
+impl PreviewPath {
+    fn cost(&self) -> usize {
+        self.costs.last().copied().unwrap_or(0)
+    }
+
+    /// Returns true if there is a path that can be walked with the given AP
+    fn in_range(&self, ap: usize) -> bool {
+        !self.path.is_empty() && self.cost() <= ap
+    }
 }
 
 fn update_pointer(
@@ -242,19 +434,25 @@ fn update_pointer(
         (&ActionState<PointerActions>, &mut Transform, &PointerObject),
         Without<CurrentPlayer>,
     >,
-    current_players: Single<&Transform, With<CurrentPlayer>>,
+    current_players: Single<(&Transform, &Stats, &Team, Has<Claimed>), With<CurrentPlayer>>,
+    modifiers: Res<Modifiers>,
     mut commands: Commands,
 ) {
-    let start_transform = current_players.into_inner();
+    let (start_transform, stats, team, carrying) = current_players.into_inner();
     for (action_state, mut transform, pointer) in &mut query {
         if pointer.timer.finished() && action_state.axis_pair(&PointerActions::Move) != Vec2::ZERO {
             let input = action_state.axis_pair(&PointerActions::Move);
             transform.translation += Vec3::new(input.x * 8.0, input.y * 8.0, 0.0);
-            if let Ok(path) =
-                calculate_path(start_transform.translation, transform.translation, &map)
-            {
-                commands.insert_resource(PreviewPath { path });
-            }
+            let preview = PathQuery::new(start_transform.translation, transform.translation)
+                .with_team(modifiers.zone_team(stats, *team))
+                .with_surcharge(stats.step_surcharge(carrying))
+                .run(&map)
+                .map(|result| PreviewPath {
+                    path: result.path,
+                    costs: result.costs,
+                })
+                .unwrap_or_default();
+            commands.insert_resource(preview);
             dirt.0 = true;
         }
     }
@@ -278,48 +476,141 @@ fn preview_path(
         let path = path_preview.unwrap();
 
         for (index, window) in path.path.windows(2).enumerate() {
-            let color = if index < stats.ap { GREEN } else { RED };
+            let color = if path.costs[index + 1] <= stats.ap {
+                GREEN
+            } else {
+                RED
+            };
             gizmos.arrow_2d(to_world(window[0]), to_world(window[1]), color);
         }
     }
 }
 
-#[derive(Default, Reflect, GizmoConfigGroup)]
-struct PassPreviewGizmos {}
+/// Where a pass to the pointer would go if nobody stopped it
+#[derive(Resource, Default)]
+struct PassPreview {
+    /// Position of the ball after every simulation step
+    points: Vec<Vec2>,
+    /// Opponents the ball rolls past, where, and their chance to be the one that intercepts it
+    interceptions: Vec<(Entity, Vec2, f32)>,
+    /// Arcs of a lob and a chip to the pointer, raised by the height of the ball
+    lofts: Vec<Vec<Vec2>>,
+}
 
-fn preview_pass(
-    time: Res<Time<Fixed>>,
+fn update_pass_preview(
+    physics: Res<BallPhysics>,
+    map: Res<Map>,
+    curl: Res<Curl>,
+    mut preview: ResMut<PassPreview>,
     current_player_option: Option<
-        Single<(&Stats, &Transform, &Velocity), (With<CurrentPlayer>, With<Claimed>)>,
+        Single<(Entity, &Stats, &Transform, &Velocity), (With<CurrentPlayer>, With<Claimed>)>,
     >,
-    query: Option<Single<&Transform, (With<PointerObject>, Without<CurrentPlayer>)>>,
-    mut gizmos: Gizmos<PassPreviewGizmos>,
+    pointer: Option<Single<&Transform, (With<PointerObject>, Without<CurrentPlayer>)>>,
+    players: Query<(&Stats, &Team)>,
 ) {
-    let Some(current_player) = current_player_option else {
+    *preview = PassPreview::default();
+    let (Some(current_player), Some(pointer)) = (current_player_option, pointer) else {
         return;
     };
-    let Some(pointer) = query else {
+    let (entity, stats, transform, velocity) = current_player.into_inner();
+    let Ok((_, own_team)) = players.get(entity) else {
         return;
     };
-    let (stats, transform, velocity) = current_player.into_inner();
-    let mut kick_vel = calculate_kick_velocity(
-        stats.passing_skill,
-        transform.translation.truncate(),
+    let position = transform.translation.truncate();
+    let kick_velocity = calculate_kick_velocity(
+        stats.pass_range(),
+        position,
         pointer.translation.truncate(),
-        time.delta_secs(),
         velocity.0,
+        &physics,
+    );
+
+    // the preview shows the intended curl, the actual one depends on the passing skill
+    let spin = calculate_spin(curl.0, stats.passing_skill, 0.0);
+    let trajectory = simulate_ball(
+        compensate_curl(BallState::rolling(position, kick_velocity), spin, &physics),
+        &physics,
+        &map,
+        &[entity],
+        |_| false,
     );
 
-    let mut end_point = Vec2::ZERO;
-    while kick_vel.length() > 0.1 {
-        end_point += kick_vel * time.delta_secs();
-        kick_vel *= 0.9;
+    // the ball only reaches a player if nobody before them stopped it
+    let mut reach_chance = 1.0;
+    for event in &trajectory.events {
+        let BallEvent::Passed(player, at) = event else {
+            continue;
+        };
+        let Ok((player_stats, team)) = players.get(*player) else {
+            continue;
+        };
+        if team != own_team {
+            preview
+                .interceptions
+                .push((*player, *at, reach_chance * player_stats.block_chance()));
+        }
+        reach_chance *= 1.0 - player_stats.block_chance();
+    }
+    preview.points = trajectory.points;
+
+    for loft in [Loft::Lob, Loft::Chip] {
+        let state = calculate_loft(
+            stats.pass_range(),
+            position,
+            pointer.translation.truncate(),
+            loft,
+            &physics,
+        )
+        .with_spin(spin);
+        let trajectory = simulate_ball(state, &physics, &map, &[entity], |_| false);
+        let arc = trajectory
+            .points
+            .iter()
+            .zip(&trajectory.heights)
+            .map(|(point, height)| *point + Vec2::Y * height * TILE_SIZE)
+            .collect();
+        preview.lofts.push(arc);
     }
+}
 
-    gizmos.line_2d(transform.translation.truncate(), end_point, WHITE);
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct PassPreviewGizmos {}
+
+fn preview_pass(preview: Res<PassPreview>, mut gizmos: Gizmos<PassPreviewGizmos>) {
+    let Some(end) = preview.points.last() else {
+        return;
+    };
+    gizmos.linestrip_2d(preview.points.iter().copied(), WHITE);
+    for arc in &preview.lofts {
+        gizmos.linestrip_2d(arc.iter().copied(), YELLOW.with_alpha(0.5));
+    }
+    gizmos.circle_2d(Isometry2d::from_translation(*end), 3.0, WHITE);
+    for (_, position, chance) in &preview.interceptions {
+        let color = YELLOW.mix(&RED, *chance);
+        gizmos.circle_2d(Isometry2d::from_translation(*position), 5.0, color);
+    }
 }
 
-#[derive(Component, Reflect, Debug)]
+/// Stamina of a fully rested player
+const MAX_STAMINA: f32 = 100.0;
+/// Below this stamina a player starts to lose strength
+const TIRED: f32 = 50.0;
+/// Stamina every player recovers at the start of a round
+const STAMINA_RECOVERY: f32 = 5.0;
+/// Additional stamina recovered per AP left unused in the last round
+const REST_STAMINA_PER_AP: f32 = 1.0;
+/// Stamina spent per AP of movement
+const STEP_STAMINA: f32 = 1.0;
+/// Stamina spent on every kick, pass or shot
+const KICK_STAMINA: f32 = 3.0;
+/// Experience needed per level to reach the next one
+const XP_PER_LEVEL: usize = 100;
+/// How often a player can lower the cost of their class ability
+const MAX_MASTERY: usize = 2;
+/// Added to the dribbling roll of a showboat
+const SHOWBOAT_BONUS: f32 = 0.2;
+
+#[derive(Component, Reflect, Clone, Debug)]
 #[reflect(Component)]
 pub struct Stats {
     ap: usize,
@@ -328,17 +619,34 @@ pub struct Stats {
     passing_skill: f32,
     wit: f32,
     defense: f32,
+    /// Grows with every opponent dribbled past
+    dribbling: f32,
+    /// Chance to win the ball in the air, defenders and attackers are best at it
+    heading: f32,
+    /// Drains with every step and kick, a tired player regains less AP, kicks softer and defends worse
+    stamina: f32,
+    /// Lowers every stat that depends on the condition of the player
+    injury: Option<Injury>,
+    level: usize,
+    /// Experience towards the next level
+    xp: usize,
+    /// Levels that weren't spent on an improvement yet
+    level_ups: usize,
+    /// Lowers the AP cost of the class ability
+    mastery: usize,
+    traits: Vec<Trait>,
     pub initiative: u8,
 }
 
 impl Stats {
-    fn from_class(class: &CharacterClass, position: usize, sampler: &mut ChaCha8Rng) -> Self {
+    pub fn from_class(class: &CharacterClass, position: usize, sampler: &mut ChaCha8Rng) -> Self {
         let (ap, kick_strength, passing_skill) = (10, 15.0, 50.0);
         let weights = match class {
             CharacterClass::Goalkeeper => [0.5, 1.0, 1.5, 1.0, 2.0],
             CharacterClass::CentralDefender => [1.0, 1.0, 1.5, 0.5, 2.0],
             CharacterClass::Midfielder => [1.5, 1.0, 2.0, 0.5, 1.0],
             CharacterClass::Attacker => [1.5, 1.0, 1.0, 2.0, 0.5],
+            CharacterClass::Winger => [2.0, 1.0, 1.5, 1.5, 0.5],
         };
 
         let random_values: [f32; 5] = sampler.random();
@@ -349,6 +657,22 @@ impl Stats {
         let sum: f32 = normalized.iter().sum();
         normalized = normalized.map(|element| element / sum);
 
+        let dribbling = match class {
+            CharacterClass::Goalkeeper => 0.0,
+            CharacterClass::CentralDefender => 0.05,
+            CharacterClass::Midfielder => 0.15,
+            CharacterClass::Attacker => 0.2,
+            CharacterClass::Winger => 0.25,
+        } + sampler.random_range(0.0..0.1);
+        // strong and defensive players jump higher
+        let heading = match class {
+            CharacterClass::Goalkeeper => 0.0,
+            CharacterClass::CentralDefender => 0.25,
+            CharacterClass::Midfielder => 0.1,
+            CharacterClass::Attacker => 0.25,
+            CharacterClass::Winger => 0.1,
+        } + (normalized[1] + normalized[4]) * 0.5;
+
         Stats {
             ap: ap + (normalized[0] * 6.0) as usize,
             intial_ap: ap + (normalized[0] * 6.0) as usize,
@@ -356,12 +680,231 @@ impl Stats {
             passing_skill: passing_skill + normalized[2] * 30.0,
             wit: normalized[3],
             defense: normalized[4],
+            dribbling,
+            heading,
+            stamina: MAX_STAMINA,
+            injury: None,
+            level: 1,
+            xp: 0,
+            level_ups: 0,
+            mastery: 0,
+            traits: Trait::roll(sampler).into_iter().collect(),
             initiative: position as u8,
         }
     }
 
-    pub fn reset_ap(&mut self) {
-        self.ap += self.intial_ap;
+    /// Share of their strength a player can still use, drops once they are tired or injured
+    pub fn condition(&self) -> f32 {
+        let impairment = self.injury.map_or(1.0, |injury| injury.impairment());
+        let freshness = if self.is_tired() {
+            0.5 + 0.5 * self.stamina / TIRED
+        } else {
+            1.0
+        };
+        freshness * impairment
+    }
+
+    /// Returns true once the stamina dropped low enough to lower the condition
+    pub fn is_tired(&self) -> bool {
+        self.stamina < TIRED
+    }
+
+    /// Adds an injury, a player that already is injured keeps the worse one
+    pub fn injure(&mut self, injury: Injury) -> Injury {
+        let worst = match self.injury {
+            Some(current) if current.severity >= injury.severity => current,
+            _ => injury,
+        };
+        self.injury = Some(worst);
+        worst
+    }
+
+    pub fn kick_strength(&self) -> f32 {
+        self.kick_strength * self.condition()
+    }
+
+    pub fn defense(&self) -> f32 {
+        self.defense * self.condition()
+    }
+
+    pub fn has_trait(&self, quirk: Trait) -> bool {
+        self.traits.contains(&quirk)
+    }
+
+    /// Furthest a pass or loft of this player goes in tiles
+    pub fn pass_range(&self) -> f32 {
+        self.traits.iter().fold(self.passing_skill, |range, quirk| {
+            range * quirk.pass_range()
+        })
+    }
+
+    /// Chance of this player blocking a ball that passes them or evading a kick
+    pub fn block_chance(&self) -> f32 {
+        self.traits
+            .iter()
+            .fold(self.defense(), |chance, quirk| {
+                chance * quirk.block_chance()
+            })
+            .min(1.0)
+    }
+
+    /// Multiplier of the chance of getting injured
+    pub fn fragility(&self) -> f32 {
+        self.traits
+            .iter()
+            .map(|quirk| quirk.injury_chance())
+            .product()
+    }
+
+    /// Additional AP every step takes, showboats waste one on every step with the ball
+    pub fn step_surcharge(&self, carrying: bool) -> usize {
+        usize::from(carrying && self.has_trait(Trait::Showboat))
+    }
+
+    /// Team whose zones of control this player respects, elusive players ignore them
+    pub fn zone_team(&self, team: Team) -> Option<Team> {
+        (!self.has_trait(Trait::Elusive)).then_some(team)
+    }
+
+    /// Drains stamina for a step that cost `ap`, sprints cost twice as much
+    pub fn tire_from_step(&mut self, ap: usize, sprinting: bool) {
+        let factor = if sprinting { 2.0 } else { 1.0 };
+        self.tire(ap as f32 * STEP_STAMINA * factor);
+    }
+
+    pub fn tire_from_kick(&mut self) {
+        self.tire(KICK_STAMINA);
+    }
+
+    fn tire(&mut self, amount: f32) {
+        self.stamina = (self.stamina - amount).max(0.0);
+    }
+
+    /// Chance of this defender stopping a runner that moves past them
+    pub fn tackle_chance(&self, runner: &Stats) -> f32 {
+        const TACKLE_CHANCE: f32 = 0.5;
+        if self.defense() + runner.wit <= 0.0 {
+            return 0.0;
+        }
+        TACKLE_CHANCE * self.defense() / (self.defense() + runner.wit)
+    }
+
+    /// Contested roll of a ball carrier trying to get past this defender, returns true if the defender wins
+    pub fn wins_dribble_contest(&self, carrier: &Stats, sampler: &mut ChaCha8Rng) -> bool {
+        let defender_roll = sampler.random_range(0.0..=1.0) * self.defense();
+        let flair = if carrier.has_trait(Trait::Showboat) {
+            SHOWBOAT_BONUS
+        } else {
+            0.0
+        };
+        let carrier_roll =
+            sampler.random_range(0.0..=1.0) * (carrier.wit + carrier.dribbling + flair);
+        defender_roll > carrier_roll
+    }
+
+    /// Chance of this keeper diving to a shot that crosses the goal line `distance` tiles away
+    pub fn dive_chance(&self, distance: f32) -> f32 {
+        const DIVE_RANGE: f32 = 2.0;
+        if distance > DIVE_RANGE {
+            return 0.0;
+        }
+        (2.0 * self.defense() * (1.0 - distance / (DIVE_RANGE + 1.0))).clamp(0.0, 1.0)
+    }
+
+    /// Every successful dribble makes the carrier a little better at it
+    pub fn improve_dribbling(&mut self) {
+        const DRIBBLE_GROWTH: f32 = 0.01;
+        self.dribbling = (self.dribbling + DRIBBLE_GROWTH).min(1.0);
+    }
+
+    /// Makes a player of a stronger or weaker opponent, `factor` of 1.0 keeps the stats
+    pub fn strengthen(&mut self, factor: f32) {
+        self.kick_strength *= factor;
+        self.passing_skill *= factor;
+        self.wit = (self.wit * factor).min(1.0);
+        self.defense = (self.defense * factor).min(1.0);
+        self.dribbling = (self.dribbling * factor).min(1.0);
+        self.heading = (self.heading * factor).min(1.0);
+    }
+
+    /// Returns true if a serious injury keeps the player out of the next match
+    pub fn is_sidelined(&self) -> bool {
+        self.injury.is_some_and(|injury| injury.matches_out > 0)
+    }
+
+    /// Gets the player ready for the next match, only serious injuries outlast a match
+    pub fn recover(&mut self) {
+        self.stamina = MAX_STAMINA;
+        self.ap = self.intial_ap;
+        self.injury = self.injury.filter(|injury| injury.matches_out > 0);
+    }
+
+    /// Counts down the injury of a player that sat out a match
+    pub fn miss_match(&mut self) {
+        if let Some(injury) = &mut self.injury {
+            injury.matches_out = injury.matches_out.saturating_sub(1);
+        }
+        self.recover();
+    }
+
+    /// Adds experience and returns how many levels the player gained
+    pub fn gain_xp(&mut self, xp: usize) -> usize {
+        self.xp += xp;
+        let mut gained = 0;
+        while self.xp >= self.level * XP_PER_LEVEL {
+            self.xp -= self.level * XP_PER_LEVEL;
+            self.level += 1;
+            self.level_ups += 1;
+            gained += 1;
+        }
+        gained
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn level_ups(&self) -> usize {
+        self.level_ups
+    }
+
+    /// Spends a level up, returns false if there is none or the improvement is maxed out
+    pub fn improve(&mut self, improvement: Improvement) -> bool {
+        if self.level_ups == 0 {
+            return false;
+        }
+        match improvement {
+            Improvement::KickStrength => self.kick_strength += 2.0,
+            Improvement::Passing => self.passing_skill += 5.0,
+            Improvement::Defense => self.defense = (self.defense + 0.05).min(1.0),
+            Improvement::Dribbling => self.dribbling = (self.dribbling + 0.05).min(1.0),
+            Improvement::Heading => self.heading = (self.heading + 0.05).min(1.0),
+            Improvement::Ap => self.intial_ap += 1,
+            Improvement::Mastery if self.mastery < MAX_MASTERY => self.mastery += 1,
+            Improvement::Mastery => return false,
+            Improvement::Learn(quirk)
+                if self.traits.len() < MAX_TRAITS && !self.has_trait(quirk) =>
+            {
+                self.traits.push(quirk)
+            }
+            Improvement::Learn(_) => return false,
+        }
+        self.level_ups -= 1;
+        true
+    }
+
+    /// AP the class ability costs this player
+    pub fn ability_cost(&self, ability: ClassAbility) -> usize {
+        ability.ap_cost().saturating_sub(self.mastery).max(1)
+    }
+
+    /// Starts a new round, AP that weren't used are spent resting instead of being saved up.
+    ///
+    /// `bonus` AP come on top of what the condition of the player allows.
+    pub fn reset_ap(&mut self, bonus: usize) {
+        let rest = self.ap as f32 * REST_STAMINA_PER_AP;
+        self.stamina = (self.stamina + STAMINA_RECOVERY + rest).min(MAX_STAMINA);
+        self.ap = (self.intial_ap as f32 * self.condition()).round() as usize + bonus;
     }
 }
 
@@ -369,16 +912,36 @@ impl std::fmt::Display for Stats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "AP: {} | KICK STRENGTH: {:.0}\nPASSING SKILL: {:.0} | WIT {:.2}%\nDEFENSE: {:.2}%",
+            "AP: {} | KICK STRENGTH: {:.0}\nPASSING SKILL: {:.0} | WIT {:.2}%\nDEFENSE: {:.2}% | DRIBBLING: {:.2}%\nHEADING: {:.2}% | STAMINA: {:.0}%\nLEVEL: {} | XP: {}/{}{}{}{}",
             self.ap,
-            self.kick_strength,
+            self.kick_strength(),
             self.passing_skill,
             self.wit * 100.0,
-            self.defense * 100.0
+            self.defense() * 100.0,
+            self.dribbling * 100.0,
+            self.heading * 100.0,
+            self.stamina / MAX_STAMINA * 100.0,
+            self.level,
+            self.xp,
+            self.level * XP_PER_LEVEL,
+            if self.level_ups > 0 {
+                format!(" | LEVEL UPS: {}", self.level_ups)
+            } else {
+                String::new()
+            },
+            self.injury
+                .map(|injury| format!("\nINJURY: {}", injury))
+                .unwrap_or_default(),
+            if self.traits.is_empty() {
+                String::new()
+            } else {
+                let traits: Vec<_> = self.traits.iter().map(|quirk| quirk.label()).collect();
+                format!("\nTRAITS: {}", traits.join(", "))
+            }
         )
     }
 }
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct Velocity(Vec2);
+pub struct Velocity(Vec2);
@@ -2,16 +2,22 @@ use std::{cmp::Reverse, collections::HashMap};
 
 use bevy::prelude::*;
 use priority_queue::PriorityQueue;
+use rand::Rng;
 
 use crate::{
     AppSet,
     entities::{Interactable, Map},
+    relics::Modifiers,
     to_ivec2, to_world,
+    ui::LogEvent,
 };
 
 use super::{
-    PointerIsDirty, Stats, Team, Velocity,
-    actions::{Action, ActionQueue},
+    PointerIsDirty, Sampler, Stats, Team, Velocity,
+    actions::{Action, ActionQueue, Claimed, ClaimedBy, InHands, Kicked},
+    experience::{Experience, TACKLE_XP},
+    physics::BallPhysics,
+    reactions::Overwatch,
 };
 
 pub fn plugin(app: &mut App) {
@@ -19,43 +25,99 @@ pub fn plugin(app: &mut App) {
         Update,
         (
             tick_path.in_set(AppSet::TickTimers),
-            follow_path.in_set(AppSet::Update),
+            (tackle_runners, follow_path).chain().in_set(AppSet::Update),
         ),
     )
     .add_observer(on_move_end);
 }
 
+/// Triggered on a player whose move came to an end, unlike removing [`CalculatedPath`] it isn't triggered by a despawn
+#[derive(Event)]
+pub struct MoveEnded;
+
 #[derive(Component)]
 pub struct CalculatedPath {
     pub path: Vec<IVec2>,
+    /// Accumulated cost to reach each tile of [`Self::path`]
+    costs: Vec<usize>,
     timer: Timer,
     current: usize,
+    /// Opponents that already tried to tackle during this move
+    reacted: Vec<Entity>,
 }
 
 impl CalculatedPath {
-    pub fn new(path: Vec<IVec2>, duration: f32) -> Self {
+    pub fn new(result: PathResult, duration: f32) -> Self {
         CalculatedPath {
-            path,
+            path: result.path,
+            costs: result.costs,
             timer: Timer::from_seconds(duration, TimerMode::Repeating),
             current: 1,
+            reacted: vec![],
         }
     }
 
-    pub fn next(&mut self) -> Option<IVec2> {
+    /// Returns the next tile and the AP it takes to step onto it
+    pub fn next(&mut self) -> Option<(IVec2, usize)> {
         if self.current < self.path.len() {
-            let next = Some(self.path[self.current]);
+            let next = self.path[self.current];
+            let cost = self.costs[self.current] - self.costs[self.current - 1];
             self.current += 1;
-            return next;
+            return Some((next, cost));
         }
         None
     }
+
+    /// Tiles before and after the one the runner stands on, as long as they are neither at the start nor the end of the move
+    fn passing_step(&self) -> Option<(IVec2, IVec2)> {
+        (self.current >= 2 && self.current < self.path.len())
+            .then(|| (self.path[self.current - 2], self.path[self.current]))
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PathError {
-    Failed,
+    /// There is no way to the target within the allowed cost
+    Unreachable,
+    /// The target lies outside of the field
+    OutOfBounds,
+    /// The target is a tile nobody can stand on
+    TargetBlocked,
 }
 
-const DIRECTIONS: [IVec2; 8] = [
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            PathError::Unreachable => "the target is out of reach",
+            PathError::OutOfBounds => "the target is out of bounds",
+            PathError::TargetBlocked => "the target is blocked",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PathResult {
+    /// Every tile from start to target, including both
+    pub path: Vec<IVec2>,
+    /// Accumulated cost to reach each tile of [`Self::path`]
+    pub costs: Vec<usize>,
+}
+
+/// Cost of a straight step in AP
+pub const STRAIGHT_COST: usize = 1;
+/// Cost of a diagonal step in AP
+pub const DIAGONAL_COST: usize = 1;
+/// Additional cost for walking over a tile that is occupied by somebody else
+pub const OCCUPIED_COST: usize = 10;
+/// Additional cost for stepping out of a tile next to an opponent
+pub const ZONE_COST: usize = 1;
+/// Steps of a move before the runner starts to sprint
+const SPRINT_AFTER: usize = 5;
+/// How far a ball rolls after the carrier loses it, in tiles
+const LOOSE_BALL_DISTANCE: f32 = 1.5;
+
+pub const DIRECTIONS: [IVec2; 8] = [
     IVec2::X,
     IVec2::ONE,
     IVec2::Y,
@@ -66,69 +128,195 @@ const DIRECTIONS: [IVec2; 8] = [
     IVec2::new(1, -1),
 ];
 
-pub fn calculate_path(start: Vec3, target: Vec3, map: &Map) -> Result<Vec<IVec2>, PathError> {
-    let start_position = to_ivec2(start);
-    let target_position = to_ivec2(target);
+/// Octile distance, which never overestimates the cost on an 8-connected grid
+pub fn octile_distance(a: IVec2, b: IVec2) -> usize {
+    let delta = (a - b).abs();
+    let (min, max) = (delta.min_element() as usize, delta.max_element() as usize);
+    STRAIGHT_COST * (max - min) + DIAGONAL_COST * min
+}
+
+/// Returns true if nobody can ever stand on this tile
+pub fn is_blocked(position: IVec2, map: &Map) -> bool {
+    map.get(&position).is_some_and(|entities| {
+        entities.iter().any(|(_, interactable)| {
+            matches!(interactable, Interactable::Wall | Interactable::Goal(_))
+        })
+    })
+}
 
+/// Returns true if somebody is standing on this tile
+pub fn is_occupied(position: IVec2, map: &Map) -> bool {
+    map.get(&position).is_some_and(|entities| {
+        entities
+            .iter()
+            .any(|(_, interactable)| interactable == &Interactable::Person)
+    })
+}
+
+/// Returns every tile that can be stepped on from `position` together with the cost of the step.
+///
+/// Diagonal steps may not cut the corner of a wall or goal post.
+/// Walking over an occupied tile costs an additional [`OCCUPIED_COST`], which is up to the caller.
+pub fn neighbors(position: IVec2, map: &Map) -> Vec<(IVec2, usize)> {
+    let mut neighbors = vec![];
+    for direction in DIRECTIONS {
+        let neighbor = position + direction;
+        if !map.bounds.contains(neighbor) || is_blocked(neighbor, map) {
+            continue;
+        }
+        let diagonal = direction.x != 0 && direction.y != 0;
+        if diagonal
+            && (is_blocked(position + IVec2::new(direction.x, 0), map)
+                || is_blocked(position + IVec2::new(0, direction.y), map))
+        {
+            continue;
+        }
+        let cost = if diagonal {
+            DIAGONAL_COST
+        } else {
+            STRAIGHT_COST
+        };
+        neighbors.push((neighbor, cost));
+    }
+    neighbors
+}
+
+/// Additional cost for leaving `position` when moving as part of `team`
+pub fn zone_cost(position: IVec2, team: Option<Team>, map: &Map) -> usize {
+    match team {
+        Some(team) if map.in_opposing_zone(position, team) => ZONE_COST,
+        _ => 0,
+    }
+}
+
+/// Dijkstra flood that returns the cost to end a move on every tile reachable within `max_cost`.
+///
+/// Occupied tiles can be reached, e.g. to engage whoever stands there,
+/// but walking on past them costs an additional [`OCCUPIED_COST`].
+/// Every step costs `surcharge` on top, see [`Stats::step_surcharge`].
+pub fn flood(
+    start: IVec2,
+    team: Option<Team>,
+    surcharge: usize,
+    max_cost: usize,
+    map: &Map,
+) -> HashMap<IVec2, usize> {
     let mut frontier: PriorityQueue<IVec2, Reverse<usize>> = PriorityQueue::new();
     let mut cost_so_far: HashMap<IVec2, usize> = HashMap::new();
-    let mut came_from: HashMap<IVec2, Option<IVec2>> = HashMap::new();
 
-    frontier.push(start_position, Reverse(0));
-    cost_so_far.insert(start_position, 0);
-    loop {
-        let Some((current_coordinates, _current_priority)) = frontier.pop() else {
-            return Err(PathError::Failed);
-        };
+    frontier.push(start, Reverse(0));
+    cost_so_far.insert(start, 0);
+    while let Some((current, Reverse(current_cost))) = frontier.pop() {
+        let mut leave_cost = current_cost + zone_cost(current, team, map);
+        if current != start && is_occupied(current, map) {
+            leave_cost += OCCUPIED_COST;
+        }
+        for (neighbor, cost) in neighbors(current, map) {
+            let new_cost = leave_cost + cost + surcharge;
+            if new_cost > max_cost {
+                continue;
+            }
+            if cost_so_far
+                .get(&neighbor)
+                .is_none_or(|&known_cost| new_cost < known_cost)
+            {
+                cost_so_far.insert(neighbor, new_cost);
+                frontier.push(neighbor, Reverse(new_cost));
+            }
+        }
+    }
+    cost_so_far
+}
+
+/// A reusable A* query from one tile to another
+pub struct PathQuery {
+    start: IVec2,
+    target: IVec2,
+    max_cost: Option<usize>,
+    team: Option<Team>,
+    surcharge: usize,
+}
+
+impl PathQuery {
+    pub fn new(start: Vec3, target: Vec3) -> Self {
+        PathQuery {
+            start: to_ivec2(start),
+            target: to_ivec2(target),
+            max_cost: None,
+            team: None,
+            surcharge: 0,
+        }
+    }
+
+    /// Makes the search respect the zones of control of every other team
+    pub fn with_team(mut self, team: impl Into<Option<Team>>) -> Self {
+        self.team = team.into();
+        self
+    }
+
+    /// Adds `surcharge` to the cost of every step, see [`Stats::step_surcharge`]
+    pub fn with_surcharge(mut self, surcharge: usize) -> Self {
+        self.surcharge = surcharge;
+        self
+    }
+
+    /// Stops the search once every remaining path would cost more than `max_cost`
+    pub fn with_max_cost(mut self, max_cost: usize) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    pub fn run(&self, map: &Map) -> Result<PathResult, PathError> {
+        if !map.bounds.contains(self.target) {
+            return Err(PathError::OutOfBounds);
+        }
+        if is_blocked(self.target, map) {
+            return Err(PathError::TargetBlocked);
+        }
 
-        if current_coordinates == target_position {
-            let mut path = vec![];
-            let mut next = target_position;
-            path.push(target_position);
-            while let Some(point_option) = came_from.get(&next) {
-                if let Some(point) = point_option {
+        let mut frontier: PriorityQueue<IVec2, Reverse<usize>> = PriorityQueue::new();
+        let mut cost_so_far: HashMap<IVec2, usize> = HashMap::new();
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+
+        frontier.push(
+            self.start,
+            Reverse(octile_distance(self.start, self.target)),
+        );
+        cost_so_far.insert(self.start, 0);
+        while let Some((current, _)) = frontier.pop() {
+            if current == self.target {
+                let mut path = vec![current];
+                let mut next = current;
+                while let Some(point) = came_from.get(&next) {
                     path.push(*point);
                     next = *point;
-                } else {
-                    break;
                 }
+                path.reverse();
+                let costs = path.iter().map(|point| cost_so_far[point]).collect();
+                return Ok(PathResult { path, costs });
             }
-            path.reverse();
-            return Ok(path);
-        }
-
-        for direction in DIRECTIONS {
-            let neighbor = current_coordinates + direction;
-            let mut cost = 1;
-            if let Some(next) = map.get(&neighbor) {
-                let mut passable = true;
-                for (_, interactable) in next {
-                    cost += match interactable {
-                        &Interactable::Person => 10,
-                        &Interactable::Goal(_) => {
-                            passable = false;
-                            100
-                        }
-                        &Interactable::Wall => {
-                            passable = false;
-                            100
-                        }
-                        _ => 0,
-                    };
+
+            let current_cost = cost_so_far[&current] + zone_cost(current, self.team, map);
+            for (neighbor, mut cost) in neighbors(current, map) {
+                if neighbor != self.target && is_occupied(neighbor, map) {
+                    cost += OCCUPIED_COST;
                 }
-                if !passable {
+                let new_cost = current_cost + cost + self.surcharge;
+                if self.max_cost.is_some_and(|max_cost| new_cost > max_cost) {
                     continue;
                 }
-            }
-            let new_cost = cost_so_far.get(&current_coordinates).unwrap() + cost;
-            let current_cost = cost_so_far.get(&neighbor);
-            if current_cost.is_none() || new_cost < *current_cost.unwrap() {
-                cost_so_far.insert(neighbor, new_cost);
-                let priority = new_cost + 1 + neighbor.distance_squared(target_position) as usize;
-                frontier.push(neighbor, Reverse(priority));
-                came_from.insert(neighbor, Some(current_coordinates));
+                if cost_so_far
+                    .get(&neighbor)
+                    .is_none_or(|&known_cost| new_cost < known_cost)
+                {
+                    cost_so_far.insert(neighbor, new_cost);
+                    let priority = new_cost + octile_distance(neighbor, self.target);
+                    frontier.push(neighbor, Reverse(priority));
+                    came_from.insert(neighbor, current);
+                }
             }
         }
+        Err(PathError::Unreachable)
     }
 }
 
@@ -138,35 +326,224 @@ fn tick_path(time: Res<Time>, mut query: Query<&mut CalculatedPath>) {
     }
 }
 
+type Runner<'a> = (
+    Entity,
+    &'a Transform,
+    &'a mut CalculatedPath,
+    &'a mut Stats,
+    &'a Team,
+    &'a Name,
+    Option<&'a Claimed>,
+    Has<InHands>,
+);
+
+type Defender<'a> = (
+    Entity,
+    &'a Stats,
+    &'a Team,
+    &'a Name,
+    &'a Transform,
+    Option<&'a Overwatch>,
+);
+
+/// Knocks the ball off `runner`, it rolls away from the player who won it
+fn knock_loose(
+    commands: &mut Commands,
+    physics: &BallPhysics,
+    runner: (Entity, &Transform),
+    ball: Entity,
+    winner: &Transform,
+) {
+    let direction = (runner.1.translation - winner.translation)
+        .truncate()
+        .normalize_or_zero();
+    commands.entity(runner.0).remove::<Claimed>();
+    commands.entity(ball).remove::<ClaimedBy>().insert(Kicked(
+        direction * physics.speed_for_distance(LOOSE_BALL_DISTANCE),
+    ));
+}
+
+/// Returns true if `tile` is part of the zone of control of a player standing on `player`
+fn in_zone(tile: IVec2, player: IVec2) -> bool {
+    tile != player && (tile - player).abs().max_element() <= 1
+}
+
+/// Gives every opponent a runner moves past one chance per move to stop them.
+///
+/// Only steps from one tile of their zone to another count, standing next to them or walking away doesn't.
+/// Runners without the ball can be tackled, ball carriers have to win a dribbling contest.
+/// Opponents on [`Overwatch`] rush out at the first runner that steps into their area, wherever it is.
+fn tackle_runners(
+    mut sampler: ResMut<Sampler>,
+    physics: Res<BallPhysics>,
+    map: Res<Map>,
+    mut runners: Query<Runner>,
+    defenders: Query<Defender, Without<CalculatedPath>>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    // watchers whose reservation is used up, but not removed yet
+    let mut spent = vec![];
+    'runners: for (entity, transform, mut path, mut stats, team, name, claimed, in_hands) in
+        &mut runners
+    {
+        // a keeper holding the ball in their hands can't be challenged
+        if in_hands || !path.timer.finished() || path.current == 0 {
+            continue;
+        }
+        for (watcher, watcher_stats, watcher_team, watcher_name, watcher_transform, overwatch) in
+            &defenders
+        {
+            let Some(overwatch) = overwatch else {
+                continue;
+            };
+            if watcher_team == team
+                || spent.contains(&watcher)
+                || !overwatch.covers(watcher_transform.translation, transform.translation)
+            {
+                continue;
+            }
+            spent.push(watcher);
+            commands.entity(watcher).remove::<Overwatch>();
+            if !overwatch.succeeds(watcher_stats.tackle_chance(&stats), &mut sampler.0) {
+                events.send(LogEvent(format!(
+                    "{} rushes out at {}, but is too late",
+                    watcher_name, name
+                )));
+                continue;
+            }
+            if let Some(claimed) = claimed {
+                knock_loose(
+                    &mut commands,
+                    &physics,
+                    (entity, transform),
+                    claimed.0,
+                    watcher_transform,
+                );
+            }
+            commands.trigger_targets(Experience(TACKLE_XP), watcher);
+            events.send(LogEvent(format!(
+                "{} rushes out and stops {}",
+                watcher_name, name
+            )));
+            // follow_path ends the move of a runner without AP
+            stats.ap = 0;
+            continue 'runners;
+        }
+
+        let current = to_ivec2(transform.translation);
+        if !map.in_opposing_zone(current, *team) {
+            continue;
+        }
+        let Some((previous, next)) = path.passing_step() else {
+            continue;
+        };
+        let adjacent = DIRECTIONS
+            .iter()
+            .filter_map(|direction| map.get(&(current + *direction)))
+            .flatten()
+            .filter(|(_, interactable)| interactable == &Interactable::Person)
+            .map(|(entity, _)| *entity)
+            .collect::<Vec<Entity>>();
+        for defender in adjacent {
+            let Ok((_, defender_stats, defender_team, defender_name, defender_transform, _)) =
+                defenders.get(defender)
+            else {
+                continue;
+            };
+            let spot = to_ivec2(defender_transform.translation);
+            if defender_team == team
+                || path.reacted.contains(&defender)
+                || !in_zone(previous, spot)
+                || !in_zone(next, spot)
+            {
+                continue;
+            }
+            path.reacted.push(defender);
+
+            if let Some(claimed) = claimed {
+                if !defender_stats.wins_dribble_contest(&stats, &mut sampler.0) {
+                    events.send(LogEvent(format!(
+                        "{} dribbles past {}",
+                        name, defender_name
+                    )));
+                    stats.improve_dribbling();
+                    continue;
+                }
+                // the ball bounces off the defender and rolls away from them
+                knock_loose(
+                    &mut commands,
+                    &physics,
+                    (entity, transform),
+                    claimed.0,
+                    defender_transform,
+                );
+                commands.trigger_targets(Experience(TACKLE_XP), defender);
+                events.send(LogEvent(format!(
+                    "{} wins the ball off {}",
+                    defender_name, name
+                )));
+                stats.ap = 0;
+                break;
+            }
+
+            if sampler.0.random_range(0.0..=1.0) < defender_stats.tackle_chance(&stats) {
+                commands.trigger_targets(Experience(TACKLE_XP), defender);
+                events.send(LogEvent(format!(
+                    "{} tackles {} and stops the run",
+                    defender_name, name
+                )));
+                // a tackled runner is done for this turn, follow_path ends the move
+                stats.ap = 0;
+                break;
+            }
+            events.send(LogEvent(format!(
+                "{} tries to tackle {}, but misses",
+                defender_name, name
+            )));
+        }
+    }
+}
+
+type Walker<'a> = (
+    &'a mut Transform,
+    &'a mut CalculatedPath,
+    Option<&'a mut Velocity>,
+    Entity,
+    &'a mut Stats,
+);
+
 fn follow_path(
+    modifiers: Res<Modifiers>,
     mut dirt: ResMut<PointerIsDirty>,
-    mut query: Query<(
-        &mut Transform,
-        &mut CalculatedPath,
-        Option<&mut Velocity>,
-        Entity,
-        &mut Stats,
-    )>,
+    mut query: Query<Walker>,
     mut commands: Commands,
 ) {
+    let sprint_after = SPRINT_AFTER.saturating_sub(modifiers.early_sprint);
     for (mut transform, mut path, velocity_option, entity, mut stats) in &mut query {
         if !path.timer.finished() {
             continue;
         }
         let next_option = path.next();
-        if let Some(next) = next_option {
-            if stats.ap > 0 {
+        let mut exhausted = false;
+        if let Some((next, cost)) = next_option {
+            if stats.ap >= cost {
                 let previous = transform.translation;
                 transform.translation = to_world(next).extend(transform.translation.z);
                 if let Some(mut velocity) = velocity_option {
                     velocity.0 += (transform.translation - previous).truncate();
                 }
-                stats.ap -= 1;
+                stats.ap -= cost;
+                // every step after the first few of a move is a sprint, a heavy pitch makes it fewer
+                stats.tire_from_step(cost, path.current > sprint_after + 1);
+            } else {
+                exhausted = true;
             }
         }
 
-        if next_option.is_none() || stats.ap == 0 {
+        if next_option.is_none() || exhausted || stats.ap == 0 {
             commands.entity(entity).remove::<CalculatedPath>();
+            commands.trigger_targets(MoveEnded, entity);
         }
         dirt.0 = true;
     }
@@ -0,0 +1,461 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    AppSet,
+    entities::{Interactable, Map},
+    to_ivec2,
+};
+
+use super::{Ball, Team, pathfinding::is_blocked};
+
+pub fn plugin(app: &mut App) {
+    let physics = BallPhysics::default();
+    app.register_type::<BallPhysics>()
+        .register_type::<Airborne>()
+        .register_type::<Spin>()
+        .insert_resource(Time::<Fixed>::from_hz(physics.rate))
+        .insert_resource(physics)
+        .add_systems(
+            PreUpdate,
+            update_fixed_rate.run_if(resource_changed::<BallPhysics>),
+        )
+        .add_systems(Update, show_ball_height.in_set(AppSet::Update));
+}
+
+/// Pixels per tile, positions are in pixels while speeds are in tiles per second
+pub const TILE_SIZE: f32 = 8.0;
+/// Share of the carriers run that is carried over into a pass
+const CARRIER_MOMENTUM: f32 = 0.5;
+/// Additional kick speed per tile of run-up
+const RUN_UP_BONUS: f32 = 0.2;
+/// Upper bound for simulations, in seconds
+const MAX_SIMULATION_SECS: f32 = 20.0;
+/// Players can stop balls below this height, in tiles
+pub const PLAYER_REACH: f32 = 1.0;
+/// Players can head balls below this height, in tiles
+const HEADER_REACH: f32 = 2.0;
+/// Balls above this height, in tiles, go over the goal
+const CROSSBAR_HEIGHT: f32 = 2.0;
+/// Sideways acceleration per level of curl, in radians per second
+const SPIN_PER_LEVEL: f32 = 0.8;
+/// Strongest curl that can be put on the ball in either direction
+pub const MAX_CURL: i8 = 2;
+/// Atlas index of the ball on the ground
+const BALL_GLYPH: usize = 7;
+/// Atlas index of the ball out of reach
+const HIGH_BALL_GLYPH: usize = 9;
+
+#[derive(Resource, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct BallPhysics {
+    /// Simulation steps per second
+    pub rate: f64,
+    /// Exponential decay of the ball speed per second
+    pub friction: f32,
+    /// Speed in tiles per second below which the ball comes to rest
+    pub rest_speed: f32,
+    /// Downward acceleration in tiles per second squared
+    pub gravity: f32,
+    /// Share of the speed a lofted ball keeps when it lands
+    pub landing_damping: f32,
+    /// Exponential decay of the spin per second
+    pub spin_decay: f32,
+    /// Acceleration of a ball in the air in tiles per second squared
+    pub wind: Vec2,
+}
+
+impl Default for BallPhysics {
+    fn default() -> Self {
+        BallPhysics {
+            rate: 64.0,
+            friction: 3.0,
+            rest_speed: 0.25,
+            gravity: 20.0,
+            landing_damping: 0.3,
+            spin_decay: 1.0,
+            wind: Vec2::ZERO,
+        }
+    }
+}
+
+impl BallPhysics {
+    /// Duration of a single simulation step in seconds
+    pub fn timestep(&self) -> f32 {
+        1.0 / self.rate as f32
+    }
+
+    /// Initial speed in tiles per second that makes the ball roll `distance` tiles
+    pub fn speed_for_distance(&self, distance: f32) -> f32 {
+        let dt = self.timestep();
+        distance * (1.0 - (-self.friction * dt).exp()) / dt
+    }
+}
+
+fn update_fixed_rate(physics: Res<BallPhysics>, mut time: ResMut<Time<Fixed>>) {
+    time.set_timestep_hz(physics.rate);
+}
+
+/// Something that happened to the ball during a step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BallEvent {
+    /// The ball bounced off a wall or goal post at the given position
+    Bounce(Vec2),
+    /// The ball rolled over a player at the given position without being stopped
+    Passed(Entity, Vec2),
+    /// The ball was stopped by a player at the given position
+    Blocked(Entity, Vec2),
+    /// The ball arrived at head height of a player at the given position
+    Header(Entity, Vec2),
+    /// The ball went into the goal of the given team
+    Goal(Team, Vec2),
+    /// A lofted ball touched the ground again at the given position
+    Landed(Vec2),
+}
+
+/// How high a ball is in the air, in tiles, and how fast it rises
+#[derive(Component, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Component)]
+pub struct Airborne {
+    pub height: f32,
+    pub vertical_velocity: f32,
+}
+
+/// How fast the ball turns its direction, in radians per second, positive values curl to the left
+#[derive(Component, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Component)]
+pub struct Spin(pub f32);
+
+/// How the ball is lifted off the ground
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loft {
+    /// A flat arc over the heads of nearby players
+    Lob,
+    /// A steep arc that drops down quickly, e.g. over the goalkeeper
+    Chip,
+}
+
+impl Loft {
+    /// Highest point of the arc in tiles
+    fn apex(&self) -> f32 {
+        match self {
+            Loft::Lob => 1.5,
+            Loft::Chip => 3.0,
+        }
+    }
+}
+
+/// Position in pixels and velocity in tiles per second of a ball, with its height in tiles
+#[derive(Debug, Clone, Copy)]
+pub struct BallState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub airborne: Airborne,
+    pub spin: Spin,
+}
+
+impl BallState {
+    /// A ball that moves along the ground
+    pub fn rolling(position: Vec2, velocity: Vec2) -> Self {
+        BallState {
+            position,
+            velocity,
+            airborne: Airborne::default(),
+            spin: Spin::default(),
+        }
+    }
+
+    pub fn with_spin(mut self, spin: Spin) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    pub fn is_airborne(&self) -> bool {
+        self.airborne.height > 0.0 || self.airborne.vertical_velocity > 0.0
+    }
+
+    pub fn is_resting(&self, physics: &BallPhysics) -> bool {
+        !self.is_airborne() && self.velocity.length() < physics.rest_speed
+    }
+}
+
+/// Advances the ball by one step of [`BallPhysics::timestep`].
+///
+/// `blocks` decides whether a player the ball rolls into stops it.
+/// Players in `ignore` are never considered, e.g. the one that kicked the ball.
+pub fn step_ball(
+    state: &mut BallState,
+    physics: &BallPhysics,
+    map: &Map,
+    ignore: &[Entity],
+    blocks: &mut impl FnMut(Entity) -> bool,
+) -> Vec<BallEvent> {
+    let mut events = vec![];
+    let total_movement = state.velocity * TILE_SIZE * physics.timestep();
+    let steps = total_movement.length().ceil() as i32;
+    let step_size = total_movement / steps.max(1) as f32;
+
+    'steps: for _ in 0..steps {
+        let next = state.position + step_size;
+        let current_position = to_ivec2(state.position.extend(0.0));
+        let next_position = to_ivec2(next.extend(0.0));
+        if let Some(entities) = map.get(&next_position) {
+            for (entity, interactable) in entities {
+                if ignore.contains(entity) {
+                    continue;
+                }
+                match interactable {
+                    Interactable::Wall => {
+                        let normal = get_wall_normal(current_position, map);
+                        state.velocity = reflect_velocity(state.velocity, normal);
+                        events.push(BallEvent::Bounce(state.position));
+                        break 'steps;
+                    }
+                    Interactable::Person => {
+                        // players only get a chance when the ball enters their tile within reach
+                        if current_position == next_position
+                            || state.airborne.height >= HEADER_REACH
+                        {
+                            continue;
+                        }
+                        if state.airborne.height >= PLAYER_REACH {
+                            // whoever wins the ball in the air decides where it goes next
+                            state.position = next;
+                            state.velocity = Vec2::ZERO;
+                            state.airborne = Airborne::default();
+                            state.spin = Spin::default();
+                            events.push(BallEvent::Header(*entity, next));
+                            break 'steps;
+                        }
+                        if blocks(*entity) {
+                            state.velocity = Vec2::ZERO;
+                            state.spin = Spin::default();
+                            events.push(BallEvent::Blocked(*entity, next));
+                            break 'steps;
+                        }
+                        events.push(BallEvent::Passed(*entity, next));
+                    }
+                    Interactable::Goal(team) => {
+                        let normal = get_wall_normal(current_position, map);
+                        let is_goal = state.airborne.height < CROSSBAR_HEIGHT
+                            && match team {
+                                Team::Enemy => normal.x < 0.0,
+                                Team::Player => normal.x > 0.0,
+                            };
+                        if is_goal {
+                            state.velocity = Vec2::ZERO;
+                            events.push(BallEvent::Goal(*team, next));
+                        } else {
+                            state.velocity = reflect_velocity(state.velocity, normal);
+                            events.push(BallEvent::Bounce(state.position));
+                        }
+                        break 'steps;
+                    }
+                    Interactable::Ball => (),
+                }
+            }
+        }
+        state.position = next;
+    }
+
+    let dt = physics.timestep();
+    // spin pushes the ball sideways, which bends its path over time
+    state.velocity = Vec2::from_angle(state.spin.0 * dt).rotate(state.velocity);
+    state.spin.0 *= (-physics.spin_decay * dt).exp();
+    if state.is_airborne() {
+        // there is no friction in the air, only the wind
+        state.velocity += physics.wind * dt;
+        let airborne = &mut state.airborne;
+        airborne.vertical_velocity -= physics.gravity * dt;
+        airborne.height += airborne.vertical_velocity * dt;
+        if airborne.height <= 0.0 {
+            *airborne = Airborne::default();
+            state.velocity *= physics.landing_damping;
+            events.push(BallEvent::Landed(state.position));
+        }
+    } else {
+        state.velocity *= (-physics.friction * dt).exp();
+    }
+    if state.is_resting(physics) {
+        state.velocity = Vec2::ZERO;
+        state.spin = Spin::default();
+    }
+    events
+}
+
+/// Every step of a simulated ball from the kick until it comes to rest
+#[derive(Debug, Clone, Default)]
+pub struct BallTrajectory {
+    /// Position of the ball after each step, starting with the initial position
+    pub points: Vec<Vec2>,
+    /// Height of the ball after each step in tiles
+    pub heights: Vec<f32>,
+    pub events: Vec<BallEvent>,
+}
+
+impl BallTrajectory {
+    /// Where the ball comes to rest
+    pub fn end(&self) -> Vec2 {
+        self.points.last().copied().unwrap_or_default()
+    }
+}
+
+/// Runs [`step_ball`] until the ball comes to rest.
+///
+/// This is the same stepping the simulation does every fixed update,
+/// so as long as `blocks` agrees with the actual rolls, so does the result.
+pub fn simulate_ball(
+    mut state: BallState,
+    physics: &BallPhysics,
+    map: &Map,
+    ignore: &[Entity],
+    mut blocks: impl FnMut(Entity) -> bool,
+) -> BallTrajectory {
+    let mut trajectory = BallTrajectory {
+        points: vec![state.position],
+        heights: vec![state.airborne.height],
+        events: vec![],
+    };
+    let max_steps = (MAX_SIMULATION_SECS * physics.rate as f32) as usize;
+    for _ in 0..max_steps {
+        if state.is_resting(physics) {
+            break;
+        }
+        let events = step_ball(&mut state, physics, map, ignore, &mut blocks);
+        trajectory.events.extend(events);
+        trajectory.points.push(state.position);
+        trajectory.heights.push(state.airborne.height);
+    }
+    trajectory
+}
+
+/// Velocity in tiles per second of a pass from `ball_position` towards `target_position`.
+///
+/// The pass is as long as the passing skill allows, part of the carriers own run is added on top.
+pub fn calculate_kick_velocity(
+    passing_skill: f32,
+    ball_position: Vec2,
+    target_position: Vec2,
+    carrier_velocity: Vec2,
+    physics: &BallPhysics,
+) -> Vec2 {
+    let diff = target_position - ball_position;
+    let direction = diff.normalize_or_zero();
+    let distance = (diff.length() / TILE_SIZE).min(passing_skill);
+
+    direction * physics.speed_for_distance(distance)
+        + carrier_velocity / TILE_SIZE * CARRIER_MOMENTUM
+}
+
+/// A lofted pass from `ball_position` that lands on `target_position`, as far as the passing skill allows
+pub fn calculate_loft(
+    passing_skill: f32,
+    ball_position: Vec2,
+    target_position: Vec2,
+    loft: Loft,
+    physics: &BallPhysics,
+) -> BallState {
+    let diff = target_position - ball_position;
+    let distance = (diff.length() / TILE_SIZE).min(passing_skill);
+    let vertical_velocity = (2.0 * physics.gravity * loft.apex()).sqrt();
+    let flight_time = 2.0 * vertical_velocity / physics.gravity;
+    BallState {
+        position: ball_position,
+        velocity: diff.normalize_or_zero() * distance / flight_time,
+        airborne: Airborne {
+            height: 0.0,
+            vertical_velocity,
+        },
+        spin: Spin::default(),
+    }
+}
+
+/// Spin for the given level of curl.
+///
+/// The better the passing skill the closer the spin is to what was intended,
+/// `roll` between -1.0 and 1.0 decides how far off it is.
+pub fn calculate_spin(curl: i8, passing_skill: f32, roll: f32) -> Spin {
+    // passing skill ranges from 50 to 80
+    let accuracy = ((passing_skill - 50.0) / 30.0).clamp(0.0, 1.0);
+    let error = roll * (1.0 - accuracy) * 0.5;
+    Spin(curl.clamp(-MAX_CURL, MAX_CURL) as f32 * SPIN_PER_LEVEL * (1.0 + error))
+}
+
+/// Turns and stretches the kick in `state` so that with `spin` the ball comes to rest where it would have without it.
+///
+/// Only the curl is aimed for, walls, players and the wind still get in the way.
+pub fn compensate_curl(state: BallState, spin: Spin, physics: &BallPhysics) -> BallState {
+    let physics = BallPhysics {
+        wind: Vec2::ZERO,
+        ..physics.clone()
+    };
+    let open_field = Map::default();
+    let straight = simulate_ball(state, &physics, &open_field, &[], |_| false).end();
+    let curled = simulate_ball(state.with_spin(spin), &physics, &open_field, &[], |_| false).end();
+    let (straight, curled) = (straight - state.position, curled - state.position);
+    if curled.length_squared() == 0.0 {
+        return state.with_spin(spin);
+    }
+    BallState {
+        velocity: Vec2::from_angle(curled.angle_to(straight)).rotate(state.velocity)
+            * (straight.length() / curled.length()),
+        ..state.with_spin(spin)
+    }
+}
+
+/// Velocity in tiles per second of something that is kicked after a run-up of `run_up` pixels
+pub fn calculate_shot_velocity(kick_strength: f32, run_up: Vec2) -> Vec2 {
+    let run_up = run_up / TILE_SIZE;
+    run_up.normalize_or_zero() * kick_strength * (1.0 + run_up.length() * RUN_UP_BONUS)
+}
+
+pub fn reflect_velocity(velocity: Vec2, normal: Vec2) -> Vec2 {
+    velocity - 2.0 * velocity.dot(normal) * normal
+}
+
+pub fn get_wall_normal(position: IVec2, map: &Map) -> Vec2 {
+    let mut normal = Vec2::ZERO;
+    if is_blocked(position + IVec2::X, map) {
+        normal += Vec2::NEG_X;
+    }
+    if is_blocked(position - IVec2::X, map) {
+        normal += Vec2::X;
+    }
+    if is_blocked(position + IVec2::Y, map) {
+        normal += Vec2::NEG_Y;
+    }
+    if is_blocked(position - IVec2::Y, map) {
+        normal += Vec2::Y;
+    }
+
+    normal.normalize_or_zero()
+}
+
+#[derive(Component)]
+pub struct BallShadow;
+
+/// Raises the ball glyph by its height and leaves the shadow on the ground
+fn show_ball_height(
+    mut balls: Query<(&mut Sprite, Option<&Airborne>, &Children), With<Ball>>,
+    mut shadows: Query<&mut Visibility, With<BallShadow>>,
+) {
+    for (mut sprite, airborne_option, children) in &mut balls {
+        let height = airborne_option.map_or(0.0, |airborne| airborne.height);
+        sprite.anchor = Anchor::Custom(Vec2::new(0.0, -height));
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = if height >= PLAYER_REACH {
+                HIGH_BALL_GLYPH
+            } else {
+                BALL_GLYPH
+            };
+        }
+        for child in children {
+            if let Ok(mut visibility) = shadows.get_mut(*child) {
+                *visibility = if height > 0.0 {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use bevy::{
+    color::palettes::css::{GREEN, RED, WHITE},
+    prelude::*,
+};
+
+use crate::{
+    AppSet,
+    entities::{Interactable, Map},
+    relics::Modifiers,
+    states::GameplayStates,
+    to_ivec2, to_world,
+};
+
+use super::{CurrentPlayer, Stats, Team, actions::Claimed, is_dirty, pathfinding};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Reachability>()
+        .insert_resource(Reachability::default())
+        .insert_gizmo_config(ReachabilityGizmos {}, GizmoConfig::default())
+        .add_systems(OnEnter(GameplayStates::PlayerTurn), update_reachability)
+        .add_systems(
+            Update,
+            (
+                update_reachability.run_if(is_dirty.or(positions_changed)),
+                preview_reachability.after(update_reachability),
+            )
+                .run_if(in_state(GameplayStates::PlayerTurn))
+                .in_set(AppSet::Update),
+        );
+}
+
+/// What the current player could do after walking onto a tile
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Engagement {
+    /// Kick or take control of the ball
+    Ball,
+    /// Kick, take control of or foul another player
+    Person,
+}
+
+/// Every tile the current player can reach with their remaining AP
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct Reachability {
+    /// Cost to end a move on each reachable tile
+    pub tiles: HashMap<IVec2, usize>,
+    pub engagements: HashMap<IVec2, Engagement>,
+    /// AP of the current player at the time of calculation
+    ap: usize,
+}
+
+fn positions_changed(query: Query<(), (With<Interactable>, Changed<Transform>)>) -> bool {
+    !query.is_empty()
+}
+
+type Mover<'a> = (Entity, &'a Transform, &'a Stats, &'a Team, Has<Claimed>);
+
+fn update_reachability(
+    map: Res<Map>,
+    modifiers: Res<Modifiers>,
+    mut reachability: ResMut<Reachability>,
+    current_player: Query<Mover, With<CurrentPlayer>>,
+) {
+    let Ok((current_entity, transform, stats, team, carrying)) = current_player.get_single() else {
+        return;
+    };
+    let tiles = pathfinding::flood(
+        to_ivec2(transform.translation),
+        modifiers.zone_team(stats, *team),
+        stats.step_surcharge(carrying),
+        stats.ap,
+        &map,
+    );
+
+    let mut engagements = HashMap::new();
+    for position in tiles.keys() {
+        let Some(entities) = map.get(position) else {
+            continue;
+        };
+        for (entity, interactable) in entities {
+            if *entity == current_entity {
+                continue;
+            }
+            match interactable {
+                Interactable::Ball => {
+                    engagements.entry(*position).or_insert(Engagement::Ball);
+                }
+                Interactable::Person => {
+                    engagements.insert(*position, Engagement::Person);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    *reachability = Reachability {
+        tiles,
+        engagements,
+        ap: stats.ap,
+    };
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct ReachabilityGizmos {}
+
+fn preview_reachability(reachability: Res<Reachability>, mut gizmos: Gizmos<ReachabilityGizmos>) {
+    const TILE: Vec2 = Vec2::splat(8.0);
+    for (position, cost) in &reachability.tiles {
+        // the closer the tile, the brighter the shade
+        let remaining = 1.0 - *cost as f32 / reachability.ap.max(1) as f32;
+        let color = GREEN.with_alpha(0.1 + 0.4 * remaining);
+        gizmos.rect_2d(
+            Isometry2d::from_translation(to_world(*position)),
+            TILE * 0.8,
+            color,
+        );
+    }
+    for (position, engagement) in &reachability.engagements {
+        let color = match engagement {
+            Engagement::Ball => WHITE,
+            Engagement::Person => RED,
+        };
+        gizmos.circle_2d(
+            Isometry2d::from_translation(to_world(*position)),
+            5.0,
+            color,
+        );
+    }
+}
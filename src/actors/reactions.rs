@@ -0,0 +1,79 @@
+use bevy::{color::palettes::css::ORANGE_RED, prelude::*};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{AppSet, to_ivec2};
+
+use super::{CurrentPlayer, Stats, Team};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Overwatch>()
+        .add_observer(reserve_ap)
+        .add_observer(expire_overwatch)
+        .add_systems(Update, show_overwatch.in_set(AppSet::Update));
+}
+
+/// How far around them a watching player reacts, in tiles
+const OVERWATCH_RANGE: i32 = 2;
+/// Least AP that has to be reserved for a reaction
+pub const MIN_OVERWATCH_AP: usize = 2;
+/// Additional chance to succeed per reserved AP
+const BONUS_PER_AP: f32 = 0.05;
+
+/// AP a player reserved at the end of their turn to react during the opponents turn.
+///
+/// A player reacts once, to the first pass or runner that comes into their area.
+/// The reactions are rolled while the opponents kick or move, in `process_kick` and `tackle_runners`.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Overwatch {
+    pub ap: usize,
+}
+
+impl Overwatch {
+    fn success_chance(&self, base: f32) -> f32 {
+        (base + self.ap as f32 * BONUS_PER_AP).min(0.95)
+    }
+
+    /// Rolls the reaction, `base` is the chance the watcher has without reserved AP
+    pub fn succeeds(&self, base: f32, sampler: &mut ChaCha8Rng) -> bool {
+        sampler.random_range(0.0..=1.0) < self.success_chance(base)
+    }
+
+    pub fn covers(&self, watcher: Vec3, position: Vec3) -> bool {
+        (to_ivec2(watcher) - to_ivec2(position)).abs().max_element() <= OVERWATCH_RANGE
+    }
+}
+
+/// Reserved AP can't be spent on anything else
+fn reserve_ap(trigger: Trigger<OnAdd, Overwatch>, mut query: Query<&mut Stats>) {
+    if let Ok(mut stats) = query.get_mut(trigger.entity()) {
+        stats.ap = 0;
+    }
+}
+
+/// Reservations that weren't used are gone once the player is up again
+fn expire_overwatch(trigger: Trigger<OnAdd, CurrentPlayer>, mut commands: Commands) {
+    commands.entity(trigger.entity()).remove::<Overwatch>();
+}
+
+/// A player on overwatch as `process_kick` and `tackle_runners` look for them
+pub type Watcher<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Overwatch,
+    &'a Stats,
+    &'a Team,
+    &'a Name,
+);
+
+fn show_overwatch(query: Query<&Transform, With<Overwatch>>, mut gizmos: Gizmos) {
+    const AREA: Vec2 = Vec2::splat((OVERWATCH_RANGE * 2 + 1) as f32 * 8.0);
+    for transform in &query {
+        gizmos.rect_2d(
+            Isometry2d::from_translation(transform.translation.truncate()),
+            AREA,
+            ORANGE_RED.with_alpha(0.4),
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    PostUpdateSet,
+    run::Run,
+    states::{AppState, GameplayStates},
+    ui::LogEvent,
+};
+
+use super::{
+    CharacterClass, CurrentPlayer, PointerIsDirty, Sampler, Stats, Team,
+    abilities::Cooldown,
+    actions::{
+        AbilitySlotMap, Claimed, ClaimedBy, CurrentActions, PlayerAbilities, PossibleAction, Slots,
+        calculate_current_actions, calculate_ui_actions,
+    },
+    enemy::enemy_ai,
+    experience::PassXp,
+    random_name,
+    tactics::Role,
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Bench>()
+        .register_type::<Booked>()
+        .register_type::<Substitutions>()
+        .init_resource::<Substitutions>()
+        .add_observer(book)
+        .add_observer(substitute)
+        .add_observer(leave_field)
+        .add_systems(OnEnter(AppState::Gameplay), spawn_benches)
+        .add_systems(
+            OnEnter(GameplayStates::EnemyTurn),
+            substitute_tired_enemies.before(enemy_ai),
+        )
+        .add_systems(
+            PostUpdate,
+            offer_substitution
+                .after(calculate_ui_actions)
+                .after(calculate_current_actions)
+                .in_set(PostUpdateSet::Move)
+                .run_if(in_state(GameplayStates::PlayerTurn)),
+        );
+}
+
+/// Substitutions every team may make during a match
+const MAX_SUBSTITUTIONS: usize = 3;
+/// Chance the referee shows a card for a foul
+const CARD_CHANCE: f32 = 0.4;
+/// Classes of the substitutes every team brings to a match
+pub const BENCH: [CharacterClass; 7] = [
+    CharacterClass::Goalkeeper,
+    CharacterClass::CentralDefender,
+    CharacterClass::CentralDefender,
+    CharacterClass::Midfielder,
+    CharacterClass::Midfielder,
+    CharacterClass::Attacker,
+    CharacterClass::Attacker,
+];
+
+/// A player that isn't on the field
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Component)]
+pub enum Bench {
+    /// Can come on as a substitute
+    Ready,
+    /// Was taken off and can't come back
+    SubstitutedOff,
+}
+
+/// A player that was shown a yellow card, a second one sends them off
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Booked;
+
+/// Triggered on a player that committed a foul, the referee decides about a card
+#[derive(Event)]
+pub struct Booking;
+
+/// Substitutions each team made during the current match
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct Substitutions {
+    used: HashMap<Team, usize>,
+}
+
+impl Substitutions {
+    pub fn remaining(&self, team: Team) -> usize {
+        MAX_SUBSTITUTIONS.saturating_sub(self.used.get(&team).copied().unwrap_or(0))
+    }
+}
+
+/// Triggered on a player on the field to replace them with a substitute
+#[derive(Event)]
+pub struct Substitute;
+
+/// Triggered on a player that has to leave the field.
+///
+/// Injured players are replaced while the team has substitutions left, players sent off never are.
+#[derive(Event)]
+pub struct LeaveField {
+    pub replace: bool,
+}
+
+pub fn spawn_benches(
+    run: Res<Run>,
+    mut sampler: ResMut<Sampler>,
+    mut substitutions: ResMut<Substitutions>,
+    mut commands: Commands,
+) {
+    *substitutions = Substitutions::default();
+    let bench = run.lineup().bench;
+    for member in bench {
+        commands.spawn((
+            Name::from(member.name.clone()),
+            member.stats.clone(),
+            member.class.clone(),
+            member.role,
+            Team::Player,
+            Bench::Ready,
+            StateScoped(AppState::Gameplay),
+        ));
+    }
+    let strength = run.opponent().map_or(1.0, |opponent| opponent.strength);
+    for (index, class) in BENCH.iter().enumerate() {
+        let mut stats = Stats::from_class(class, index, &mut sampler.0);
+        stats.strengthen(strength);
+        commands.spawn((
+            Name::from(random_name(&mut sampler.0)),
+            stats,
+            class.clone(),
+            Role::for_class(class),
+            Team::Enemy,
+            Bench::Ready,
+            StateScoped(AppState::Gameplay),
+        ));
+    }
+}
+
+type Squad<'a> = (
+    Entity,
+    &'a mut Name,
+    &'a mut Stats,
+    &'a mut CharacterClass,
+    &'a mut Role,
+    &'a Team,
+    Option<&'a Bench>,
+    Option<&'a PassXp>,
+);
+
+/// Picks the most rested substitute of a team, substitutes of the same class come first
+fn pick_substitute(squad: &Query<Squad>, team: Team, class: &CharacterClass) -> Option<Entity> {
+    squad
+        .iter()
+        .filter(|(_, _, _, _, _, bench_team, bench, _)| {
+            **bench_team == team && bench.is_some_and(|bench| *bench == Bench::Ready)
+        })
+        .max_by(|(_, _, a, a_class, ..), (_, _, b, b_class, ..)| {
+            (*a_class == class)
+                .cmp(&(*b_class == class))
+                .then(a.stamina.total_cmp(&b.stamina))
+        })
+        .map(|(entity, ..)| entity)
+}
+
+/// Swaps a player on the field with a substitute and returns both names.
+///
+/// The substitute takes over the place, the remaining AP and the turn order of the player they replace.
+fn bring_on(
+    field: Entity,
+    substitute: Entity,
+    squad: &mut Query<Squad>,
+    commands: &mut Commands,
+) -> Option<(Name, Name)> {
+    let [
+        (_, mut on_name, mut on_stats, mut on_class, mut on_role, _, _, off_pass_xp),
+        (_, mut off_name, mut off_stats, mut off_class, mut off_role, _, _, on_pass_xp),
+    ] = squad.get_many_mut([field, substitute]).ok()?;
+    // the pass experience cap belongs to the player, not to their place on the field
+    for (entity, pass_xp) in [(field, on_pass_xp), (substitute, off_pass_xp)] {
+        match pass_xp {
+            Some(pass_xp) => commands.entity(entity).insert(*pass_xp),
+            None => commands.entity(entity).remove::<PassXp>(),
+        };
+    }
+    std::mem::swap(&mut *on_name, &mut *off_name);
+    std::mem::swap(&mut *on_stats, &mut *off_stats);
+    std::mem::swap(&mut *on_class, &mut *off_class);
+    std::mem::swap(&mut *on_role, &mut *off_role);
+    std::mem::swap(&mut on_stats.ap, &mut off_stats.ap);
+    std::mem::swap(&mut on_stats.initiative, &mut off_stats.initiative);
+    Some((on_name.clone(), off_name.clone()))
+}
+
+fn substitute(
+    trigger: Trigger<Substitute>,
+    mut substitutions: ResMut<Substitutions>,
+    mut squad: Query<Squad>,
+    mut dirt: ResMut<PointerIsDirty>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity();
+    let Ok((_, _, _, class, _, team, ..)) = squad.get(entity) else {
+        return;
+    };
+    let team = *team;
+    if substitutions.remaining(team) == 0 {
+        events.send(LogEvent("There are no substitutions left".to_string()));
+        return;
+    }
+    let Some(substitute) = pick_substitute(&squad, team, class) else {
+        events.send(LogEvent("There is nobody left on the bench".to_string()));
+        return;
+    };
+    let Some((on, off)) = bring_on(entity, substitute, &mut squad, &mut commands) else {
+        return;
+    };
+    *substitutions.used.entry(team).or_default() += 1;
+    commands.entity(substitute).insert(Bench::SubstitutedOff);
+    commands.entity(entity).remove::<(Cooldown, Booked)>();
+    dirt.0 = true;
+    events.send(LogEvent(format!("{} comes on for {}", on, off)));
+}
+
+fn leave_field(
+    trigger: Trigger<LeaveField>,
+    mut substitutions: ResMut<Substitutions>,
+    mut squad: Query<Squad>,
+    field: Query<(Option<&Claimed>, Has<CurrentPlayer>)>,
+    mut next: ResMut<NextState<GameplayStates>>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity();
+    let Ok((_, name, stats, class, role, team, ..)) = squad.get(entity) else {
+        return;
+    };
+    // the player stays in the squad for the rest of the run
+    let benched = (
+        name.clone(),
+        stats.clone(),
+        class.clone(),
+        *role,
+        *team,
+        Bench::SubstitutedOff,
+        StateScoped(AppState::Gameplay),
+    );
+    let (name, team) = (name.clone(), *team);
+    let replacement = (trigger.event().replace && substitutions.remaining(team) > 0)
+        .then(|| pick_substitute(&squad, team, class))
+        .flatten();
+    if let Some((on, off)) = replacement.and_then(|substitute| {
+        let names = bring_on(entity, substitute, &mut squad, &mut commands)?;
+        commands.entity(substitute).insert(Bench::SubstitutedOff);
+        Some(names)
+    }) {
+        *substitutions.used.entry(team).or_default() += 1;
+        commands.entity(entity).remove::<(Cooldown, Booked)>();
+        events.send(LogEvent(format!(
+            "{} comes on for {}, who has to leave the field",
+            on, off
+        )));
+        return;
+    }
+
+    // nobody replaces the player, the team has to go on without them
+    let Ok((claimed, is_current)) = field.get(entity) else {
+        return;
+    };
+    if let Some(claimed) = claimed {
+        commands.entity(claimed.0).remove::<ClaimedBy>();
+    }
+    if is_current {
+        next.set(GameplayStates::Banner(team.opponent()));
+    }
+    commands.entity(entity).despawn_recursive();
+    commands.spawn(benched);
+    events.send(LogEvent(format!(
+        "{} leaves the field without a replacement",
+        name
+    )));
+}
+
+fn book(
+    trigger: Trigger<Booking>,
+    mut sampler: ResMut<Sampler>,
+    players: Query<(&Name, Has<Booked>)>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity();
+    let Ok((name, booked)) = players.get(entity) else {
+        return;
+    };
+    if sampler.0.random_range(0.0..=1.0) >= CARD_CHANCE {
+        return;
+    }
+    if booked {
+        events.send(LogEvent(format!(
+            "{} sees a second yellow card and is sent off",
+            name
+        )));
+        commands.trigger_targets(LeaveField { replace: false }, entity);
+    } else {
+        events.send(LogEvent(format!("{} is shown a yellow card", name)));
+        commands.entity(entity).insert(Booked);
+    }
+}
+
+/// Tired and booked enemies are replaced before they act
+fn substitute_tired_enemies(
+    substitutions: Res<Substitutions>,
+    current_player: Single<(Entity, &Stats, &Team, Has<Booked>), With<CurrentPlayer>>,
+    mut commands: Commands,
+) {
+    let (entity, stats, team, booked) = current_player.into_inner();
+    if *team == Team::Enemy && (stats.is_tired() || booked) && substitutions.remaining(*team) > 0 {
+        commands.trigger_targets(Substitute, entity);
+    }
+}
+
+fn offer_substitution(
+    substitutions: Res<Substitutions>,
+    current_player: Single<&Team, With<CurrentPlayer>>,
+    bench: Query<(&Bench, &Team)>,
+    mut actions: ResMut<CurrentActions>,
+    ability_slot: Single<&mut AbilitySlotMap>,
+) {
+    let team = *current_player.into_inner();
+    let remaining = substitutions.remaining(team);
+    let available = remaining > 0
+        && bench
+            .iter()
+            .any(|(bench, bench_team)| *bench == Bench::Ready && *bench_team == team);
+    actions.actions.push(PossibleAction::Command(
+        "u".to_string(),
+        format!("substitute ({} left)", remaining),
+        available,
+    ));
+    if available {
+        ability_slot
+            .into_inner()
+            .insert(Slots::Ability10, PlayerAbilities::Substitute);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{entities::Map, relics::Modifiers, to_ivec2, to_world};
+
+use super::{
+    Ball, CharacterClass, CurrentPlayer, PointerIsDirty, Stats, Team,
+    actions::ClaimedBy,
+    formations::KickoffSpot,
+    pathfinding::{MoveEnded, PathQuery, STRAIGHT_COST, is_occupied, octile_distance, zone_cost},
+    physics::TILE_SIZE,
+    substitutions::Bench,
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Instructions>()
+        .register_type::<Role>()
+        .add_observer(nudge_teammates);
+}
+
+/// Tiles a runner on a counter-attack pushes past their usual spot
+const COUNTER_RUN: f32 = 10.0;
+
+/// Tiles a nudged teammate may detour around others on the way to their spot
+const NUDGE_DETOUR: usize = 4;
+
+/// How far up the field the whole team stands
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Debug)]
+pub enum LineHeight {
+    Deep,
+    #[default]
+    Normal,
+    High,
+}
+
+impl LineHeight {
+    /// Tiles the team stands forward of its anchors
+    fn offset(&self) -> f32 {
+        match self {
+            LineHeight::Deep => -6.0,
+            LineHeight::Normal => 0.0,
+            LineHeight::High => 6.0,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            LineHeight::Deep => LineHeight::Normal,
+            LineHeight::Normal => LineHeight::High,
+            LineHeight::High => LineHeight::Deep,
+        }
+    }
+}
+
+/// How many players chase the opposing ball carrier
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Debug)]
+pub enum Pressing {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Pressing {
+    /// Players closest to the ball carrier that go after them
+    pub fn pressers(&self) -> usize {
+        match self {
+            Pressing::Low => 0,
+            Pressing::Medium => 2,
+            Pressing::High => 4,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Pressing::Low => Pressing::Medium,
+            Pressing::Medium => Pressing::High,
+            Pressing::High => Pressing::Low,
+        }
+    }
+}
+
+/// How far the team spreads towards the sidelines
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Debug)]
+pub enum Width {
+    Narrow,
+    #[default]
+    Normal,
+    Wide,
+}
+
+impl Width {
+    /// Multiplies the distance of the anchors from the middle of the field
+    fn scale(&self) -> f32 {
+        match self {
+            Width::Narrow => 0.7,
+            Width::Normal => 1.0,
+            Width::Wide => 1.3,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Width::Narrow => Width::Normal,
+            Width::Normal => Width::Wide,
+            Width::Wide => Width::Narrow,
+        }
+    }
+}
+
+/// Team-level instructions, every player of a team carries them
+#[derive(
+    Component, Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Debug,
+)]
+#[reflect(Component)]
+pub struct Instructions {
+    pub line: LineHeight,
+    pub pressing: Pressing,
+    pub width: Width,
+    /// Attacking players break forward while the ball is still in our half
+    pub counter_attack: bool,
+}
+
+impl Instructions {
+    /// Where an off-ball player of `team` wants to stand
+    pub fn target(
+        &self,
+        spot: Vec2,
+        role: Role,
+        team: Team,
+        possession: Option<Team>,
+        ball: Vec2,
+    ) -> Vec2 {
+        let forward = match team {
+            Team::Player => 1.0,
+            Team::Enemy => -1.0,
+        };
+        let in_possession = possession == Some(team);
+        let mut advance = self.line.offset() + role.offset(in_possession);
+        if self.counter_attack && in_possession && role == Role::Attacking && ball.x * forward < 0.0
+        {
+            advance += COUNTER_RUN;
+        }
+        Vec2::new(
+            spot.x + advance * forward * TILE_SIZE,
+            spot.y * self.width.scale(),
+        )
+    }
+}
+
+impl std::fmt::Display for Instructions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "LINE: {:?} | PRESSING: {:?} | WIDTH: {:?} | COUNTER: {}",
+            self.line,
+            self.pressing,
+            self.width,
+            if self.counter_attack { "ON" } else { "OFF" }
+        )
+    }
+}
+
+/// What a player does off the ball on top of the team instructions
+#[derive(
+    Component, Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Debug,
+)]
+#[reflect(Component)]
+pub enum Role {
+    /// Stays behind the ball
+    Defensive,
+    #[default]
+    Balanced,
+    /// Runs forward, most of all when the team has the ball
+    Attacking,
+}
+
+impl Role {
+    pub fn for_class(class: &CharacterClass) -> Self {
+        match class {
+            CharacterClass::Goalkeeper | CharacterClass::CentralDefender => Role::Defensive,
+            CharacterClass::Midfielder => Role::Balanced,
+            CharacterClass::Attacker | CharacterClass::Winger => Role::Attacking,
+        }
+    }
+
+    /// Tiles the player stands forward of their anchor
+    fn offset(&self, in_possession: bool) -> f32 {
+        match (self, in_possession) {
+            (Role::Defensive, _) => -4.0,
+            (Role::Balanced, true) => 3.0,
+            (Role::Balanced, false) => 0.0,
+            (Role::Attacking, true) => 8.0,
+            (Role::Attacking, false) => 2.0,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Role::Defensive => Role::Balanced,
+            Role::Balanced => Role::Attacking,
+            Role::Attacking => Role::Defensive,
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Defensive => write!(f, "DEFENSIVE"),
+            Role::Balanced => write!(f, "BALANCED"),
+            Role::Attacking => write!(f, "ATTACKING"),
+        }
+    }
+}
+
+type Teammate<'a> = (
+    Entity,
+    &'a mut Transform,
+    &'a Stats,
+    &'a Team,
+    &'a CharacterClass,
+    &'a KickoffSpot,
+    &'a Role,
+    &'a Instructions,
+);
+
+type Possession<'a> = (&'a Transform, Option<&'a ClaimedBy>);
+
+/// Players on the field that aren't moving right now
+type OffBall = (Without<Ball>, Without<CurrentPlayer>, Without<Bench>);
+
+/// Moves every other outfield player of the team one tile towards where their instructions want them after a move.
+///
+/// The step is the first one of the path there, players held in a zone of control of the opponents stay.
+fn nudge_teammates(
+    trigger: Trigger<MoveEnded>,
+    map: Res<Map>,
+    modifiers: Res<Modifiers>,
+    mut dirt: ResMut<PointerIsDirty>,
+    teams: Query<&Team>,
+    balls: Query<Possession, With<Ball>>,
+    mut teammates: Query<Teammate, OffBall>,
+) {
+    // with two balls on the field the one somebody has counts
+    let (Ok(team), Some((ball_transform, claimed_by))) = (
+        teams.get(trigger.entity()),
+        balls
+            .iter()
+            .max_by_key(|(_, claimed_by)| claimed_by.is_some()),
+    ) else {
+        return;
+    };
+    let possession = claimed_by.and_then(|claimed_by| teams.get(claimed_by.0).ok().copied());
+    let ball_position = ball_transform.translation.truncate();
+    // tiles somebody already moved onto during this nudge
+    let mut taken = HashSet::new();
+    for (entity, mut transform, stats, teammate_team, class, spot, role, instructions) in
+        &mut teammates
+    {
+        if teammate_team != team
+            || *class == CharacterClass::Goalkeeper
+            || claimed_by.is_some_and(|claimed_by| claimed_by.0 == entity)
+        {
+            continue;
+        }
+        let target = instructions.target(**spot, *role, *team, possession, ball_position);
+        let zone_team = modifiers.zone_team(stats, *team);
+        let current = to_ivec2(transform.translation);
+        if zone_cost(current, zone_team, &map) > 0 {
+            continue;
+        }
+        // give up on spots that are far out of the way rather than search the whole field
+        let max_cost =
+            octile_distance(current, to_ivec2(target.extend(0.0))) + NUDGE_DETOUR * STRAIGHT_COST;
+        let Ok(path) = PathQuery::new(transform.translation, target.extend(0.0))
+            .with_team(zone_team)
+            .with_max_cost(max_cost)
+            .run(&map)
+        else {
+            continue;
+        };
+        // the path may end on whoever stands on the target, but nobody steps on them
+        let step = path
+            .path
+            .get(1)
+            .copied()
+            .filter(|step| !taken.contains(step) && !is_occupied(*step, &map));
+        if let Some(step) = step {
+            taken.insert(step);
+            transform.translation = to_world(step).extend(transform.translation.z);
+            dirt.0 = true;
+        }
+    }
+}
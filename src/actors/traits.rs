@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use rand::{Rng, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Trait>();
+}
+
+/// Chance of a new player coming with a trait
+const TRAIT_CHANCE: f64 = 0.4;
+/// Most traits a player can have
+pub const MAX_TRAITS: usize = 3;
+
+/// A passive quirk of a player that changes how they play
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Trait {
+    /// Passes and lofts reach further
+    LongPasser,
+    /// Blocks and evades less often
+    Clumsy,
+    /// Blocks and evades a lot more often
+    IronWall,
+    /// Gets injured twice as easily
+    GlassAnkles,
+    /// Wins more dribbles, but every step with the ball costs an extra AP
+    Showboat,
+    /// Ignores the zones of control of opponents
+    Elusive,
+}
+
+/// Every trait a new player can come with
+const TRAITS: [Trait; 6] = [
+    Trait::LongPasser,
+    Trait::Clumsy,
+    Trait::IronWall,
+    Trait::GlassAnkles,
+    Trait::Showboat,
+    Trait::Elusive,
+];
+
+impl Trait {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trait::LongPasser => "Long Passer",
+            Trait::Clumsy => "Clumsy",
+            Trait::IronWall => "Iron Wall",
+            Trait::GlassAnkles => "Glass Ankles",
+            Trait::Showboat => "Showboat",
+            Trait::Elusive => "Elusive",
+        }
+    }
+
+    /// Rolls the trait a new player comes with, if any
+    pub fn roll(sampler: &mut ChaCha8Rng) -> Option<Trait> {
+        if !sampler.random_bool(TRAIT_CHANCE) {
+            return None;
+        }
+        TRAITS.choose(sampler).copied()
+    }
+
+    /// Multiplier of the pass range
+    pub fn pass_range(&self) -> f32 {
+        match self {
+            Trait::LongPasser => 1.3,
+            _ => 1.0,
+        }
+    }
+
+    /// Multiplier of the chance to block a ball or evade a kick
+    pub fn block_chance(&self) -> f32 {
+        match self {
+            Trait::Clumsy => 0.6,
+            Trait::IronWall => 1.5,
+            _ => 1.0,
+        }
+    }
+
+    /// Multiplier of the injury chance
+    pub fn injury_chance(&self) -> f32 {
+        match self {
+            Trait::GlassAnkles => 2.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl std::fmt::Display for Trait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
@@ -12,6 +12,13 @@ pub trait LoadResource {
     /// have been loaded, it will be inserted as a resource. This ensures that the resource only
     /// exists when the assets are ready.
     fn load_resource<T: Resource + Asset + Clone + FromWorld>(&mut self) -> &mut Self;
+
+    /// Like [`LoadResource::load_resource`], but the [`Resource`] itself is read from the file at `path`
+    /// by the [`AssetLoader`](bevy::asset::AssetLoader) registered for it.
+    fn load_resource_from_path<T: Resource + Asset + Clone>(
+        &mut self,
+        path: &'static str,
+    ) -> &mut Self;
 }
 
 impl LoadResource for App {
@@ -19,21 +26,36 @@ impl LoadResource for App {
         self.init_asset::<T>();
         let world = self.world_mut();
         let value = T::from_world(world);
-        let assets = world.resource::<AssetServer>();
-        let handle = assets.add(value);
-        let mut handles = world.resource_mut::<ResourceHandles>();
-        handles
-            .waiting
-            .push_back((handle.untyped(), |world, handle| {
-                let assets = world.resource::<Assets<T>>();
-                if let Some(value) = assets.get(handle.id().typed::<T>()) {
-                    world.insert_resource(value.clone());
-                }
-            }));
+        let handle = world.resource::<AssetServer>().add(value);
+        track_resource(world, handle);
+        self
+    }
+
+    fn load_resource_from_path<T: Resource + Asset + Clone>(
+        &mut self,
+        path: &'static str,
+    ) -> &mut Self {
+        self.init_asset::<T>();
+        let world = self.world_mut();
+        let handle = world.resource::<AssetServer>().load::<T>(path);
+        track_resource(world, handle);
         self
     }
 }
 
+/// Waits for the asset behind `handle` and inserts it as a [`Resource`] once it has loaded.
+fn track_resource<T: Resource + Asset + Clone>(world: &mut World, handle: Handle<T>) {
+    let mut handles = world.resource_mut::<ResourceHandles>();
+    handles
+        .waiting
+        .push_back((handle.untyped(), |world, handle| {
+            let assets = world.resource::<Assets<T>>();
+            if let Some(value) = assets.get(handle.id().typed::<T>()) {
+                world.insert_resource(value.clone());
+            }
+        }));
+}
+
 /// A function that inserts a loaded resource.
 type InsertLoadedResource = fn(&mut World, &UntypedHandle);
 
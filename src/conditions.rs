@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+use crate::relics::Modifiers;
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<MatchCondition>();
+}
+
+/// Chance of each condition turning up in a match
+const CONDITION_CHANCE: f64 = 0.25;
+/// Most conditions a single match can have
+const MAX_CONDITIONS: usize = 2;
+/// Sideways push of the wind on a ball in the air, in tiles per second squared
+const WIND_STRENGTH: f32 = 3.0;
+/// Most initiative a hostile crowd takes from one of our players whenever the next one is picked
+const CROWD_INITIATIVE: u8 = 4;
+
+/// Something about a match that neither team chose
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchCondition {
+    Rain,
+    /// Blows towards the top sideline if positive, towards the bottom one otherwise
+    Wind(i8),
+    HostileCrowd,
+    DoubleBall,
+    BiasedReferee,
+}
+
+impl MatchCondition {
+    /// Rolls the conditions of a match
+    pub fn roll(sampler: &mut ChaCha8Rng) -> Vec<MatchCondition> {
+        let wind = if sampler.random_bool(0.5) { 1 } else { -1 };
+        let mut conditions: Vec<_> = [
+            MatchCondition::Rain,
+            MatchCondition::Wind(wind),
+            MatchCondition::HostileCrowd,
+            MatchCondition::DoubleBall,
+            MatchCondition::BiasedReferee,
+        ]
+        .into_iter()
+        .filter(|_| sampler.random_bool(CONDITION_CHANCE))
+        .collect();
+        conditions.shuffle(sampler);
+        conditions.truncate(MAX_CONDITIONS);
+        conditions
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MatchCondition::Rain => "Rainy Pitch",
+            MatchCondition::Wind(_) => "Windy Day",
+            MatchCondition::HostileCrowd => "Hostile Crowd",
+            MatchCondition::DoubleBall => "Double Ball",
+            MatchCondition::BiasedReferee => "Biased Referee",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            MatchCondition::Rain => "The wet pitch slows the ball down and tires runners sooner",
+            MatchCondition::Wind(direction) if *direction > 0 => {
+                "Balls in the air drift towards the top sideline"
+            }
+            MatchCondition::Wind(_) => "Balls in the air drift towards the bottom sideline",
+            MatchCondition::HostileCrowd => {
+                "The crowd rattles our players, they lose initiative and act out of their usual order"
+            }
+            MatchCondition::DoubleBall => "A second ball is in play",
+            MatchCondition::BiasedReferee => {
+                "The referee adds stoppage time while our opponents are behind"
+            }
+        }
+    }
+
+    /// Adds the effect of the condition to the modifiers of a match
+    pub fn register(&self, modifiers: &mut Modifiers) {
+        match self {
+            MatchCondition::Rain => {
+                modifiers.ball_friction *= 1.5;
+                modifiers.early_sprint += 2;
+            }
+            MatchCondition::Wind(direction) => {
+                modifiers.wind = Vec2::Y * WIND_STRENGTH * f32::from(*direction)
+            }
+            MatchCondition::HostileCrowd => modifiers.initiative_penalty += CROWD_INITIATIVE,
+            MatchCondition::DoubleBall => modifiers.second_ball = true,
+            MatchCondition::BiasedReferee => modifiers.home_stoppage_time += 5,
+        }
+    }
+}
+
+impl std::fmt::Display for MatchCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
@@ -4,7 +4,7 @@ use crate::{actors::actions::Kicked, entities::Interactable};
 
 pub fn plugin(app: &mut App) {
     app.register_type::<KickVelocity>()
-        .insert_resource(KickVelocity(Vec2::new(500.0, 500.0)))
+        .insert_resource(KickVelocity(Vec2::new(30.0, 30.0)))
         .add_systems(Update, add_kick);
 }
 
@@ -1,8 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use bevy::prelude::*;
 
-use crate::{PostUpdateSet, actors::Team, to_ivec2};
+use crate::{
+    PostUpdateSet,
+    actors::{Team, pathfinding::DIRECTIONS},
+    to_ivec2,
+};
 
 pub fn plugin(app: &mut App) {
     app.register_type::<Map>()
@@ -22,15 +26,88 @@ pub enum Interactable {
 #[derive(Resource, Default, Deref, DerefMut, Reflect)]
 #[reflect(Resource)]
 pub struct Map {
+    #[deref]
     map: HashMap<IVec2, Vec<(Entity, Interactable)>>,
+    /// Smallest rectangle that contains every wall of the field
+    pub bounds: IRect,
+    /// Tiles next to at least one player of a team
+    pub zones: HashMap<Team, HashSet<IVec2>>,
+    /// Tiles of the goal each team defends
+    pub goals: HashMap<Team, IRect>,
+    /// Area in front of each team's goal where their keeper may use their hands
+    pub penalty_boxes: HashMap<Team, IRect>,
 }
 
-fn update_map(mut map: ResMut<Map>, query: Query<(&Transform, Entity, &Interactable)>) {
+impl Map {
+    /// Returns true if `position` is next to a player that is not part of `team`
+    pub fn in_opposing_zone(&self, position: IVec2, team: Team) -> bool {
+        self.zones
+            .iter()
+            .any(|(zone_team, tiles)| *zone_team != team && tiles.contains(&position))
+    }
+
+    /// Returns true if `position` is inside the penalty box in front of the goal of `team`
+    pub fn in_penalty_box(&self, position: IVec2, team: Team) -> bool {
+        self.penalty_boxes
+            .get(&team)
+            .is_some_and(|rect| rect.contains(position))
+    }
+}
+
+/// How far the penalty box reaches into the field from the goal line, in tiles
+const PENALTY_BOX_DEPTH: i32 = 15;
+/// How far the penalty box reaches past each goal post, in tiles
+const PENALTY_BOX_WIDTH: i32 = 12;
+
+fn update_map(
+    mut map: ResMut<Map>,
+    query: Query<(&Transform, Entity, &Interactable, Option<&Team>)>,
+) {
     map.clear();
-    for (transform, entity, interactable) in &query {
+    map.zones.clear();
+    let mut bounds: Option<IRect> = None;
+    let mut goals: HashMap<Team, IRect> = HashMap::new();
+    for (transform, entity, interactable, team_option) in &query {
         let position = to_ivec2(transform.translation);
         map.entry(position)
             .and_modify(|vec| vec.push((entity, interactable.clone())))
             .or_insert(vec![(entity, interactable.clone())]);
+        if interactable == &Interactable::Wall {
+            bounds = Some(match bounds {
+                Some(rect) => rect.union_point(position),
+                None => IRect::from_corners(position, position),
+            });
+        }
+        if let Interactable::Goal(team) = interactable {
+            goals
+                .entry(*team)
+                .and_modify(|rect| *rect = rect.union_point(position))
+                .or_insert(IRect::from_corners(position, position));
+        }
+        if let Some(team) = team_option {
+            let zone = map.zones.entry(*team).or_default();
+            for direction in DIRECTIONS {
+                zone.insert(position + direction);
+            }
+        }
     }
+    map.bounds = bounds.unwrap_or_default();
+
+    let center = map.bounds.center();
+    map.penalty_boxes = goals
+        .iter()
+        .map(|(team, goal)| {
+            // the box reaches from the goal line towards the middle of the field
+            let depth = if goal.center().x < center.x {
+                PENALTY_BOX_DEPTH
+            } else {
+                -PENALTY_BOX_DEPTH
+            };
+            let widened = IVec2::new(0, PENALTY_BOX_WIDTH);
+            let rect = IRect::from_corners(goal.min - widened, goal.max + widened)
+                .union_point(goal.center() + IVec2::new(depth, 0));
+            (*team, rect)
+        })
+        .collect();
+    map.goals = goals;
 }
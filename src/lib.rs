@@ -6,8 +6,12 @@ use bevy::{
 
 mod actors;
 mod asset_tracking;
+mod conditions;
 mod entities;
 mod map;
+mod profile;
+mod relics;
+mod run;
 mod states;
 mod theme;
 mod ui;
@@ -37,6 +41,10 @@ impl Plugin for SuperRogueSoccerPlugin {
             actors::plugin,
             states::plugin,
             map::plugin,
+            profile::plugin,
+            run::plugin,
+            relics::plugin,
+            conditions::plugin,
             entities::plugin,
             ui::plugin,
             #[cfg(feature = "debug")]
@@ -79,3 +79,15 @@ pub const FIELD: &str = "#######################################################
 #                                                                                                                   #
 #                                                                                                                   #
 #####################################################################################################################";
+
+/// Trees of the park pitch as offsets in tiles from the center spot, every tree covers two by two tiles
+pub const PARK_TREES: [(i32, i32); 8] = [
+    (-22, 14),
+    (22, 14),
+    (-22, -14),
+    (22, -14),
+    (-8, 26),
+    (8, 26),
+    (-8, -26),
+    (8, -26),
+];
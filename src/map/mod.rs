@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{GlyphAsset, actors::Team, entities::Interactable};
+use crate::{GlyphAsset, actors::Team, entities::Interactable, run::Run, states::AppState};
 
 mod field;
 
@@ -11,26 +12,69 @@ const FULL: u32 = 13 * 16 + 11;
 const GOAL_NET: u32 = 13 * 16 + 13;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins(TilemapPlugin);
+    app.register_type::<Pitch>().add_plugins(TilemapPlugin);
 }
 
-pub fn spawn_field(glyph: Res<GlyphAsset>, mut commands: Commands) {
-    let height = field::FIELD.lines().count();
-    let width = field::FIELD.lines().next().unwrap().chars().count();
+/// Where the matches of a run are played
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Debug)]
+pub enum Pitch {
+    #[default]
+    Stadium,
+    /// Trees stand in the way of players and the ball
+    Park,
+}
+
+impl Pitch {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Pitch::Stadium => "Stadium",
+            Pitch::Park => "Park",
+        }
+    }
+
+    /// Rows of the pitch, see [`field::FIELD`] for the characters
+    fn layout(&self) -> Vec<Vec<char>> {
+        let mut rows: Vec<Vec<char>> = field::FIELD
+            .lines()
+            .map(|line| line.chars().collect())
+            .collect();
+        if *self == Pitch::Park {
+            let center = IVec2::new(rows[0].len() as i32 / 2, rows.len() as i32 / 2);
+            for (x, y) in field::PARK_TREES {
+                for offset in [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE] {
+                    let tile = center + IVec2::new(x, y) + offset;
+                    rows[tile.y as usize][tile.x as usize] = '#';
+                }
+            }
+        }
+        rows
+    }
+}
+
+impl std::fmt::Display for Pitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+pub fn spawn_field(run: Res<Run>, glyph: Res<GlyphAsset>, mut commands: Commands) {
+    let layout = run.pitch.layout();
+    let height = layout.len();
+    let width = layout[0].len();
     let map_size = TilemapSize {
         x: width as u32,
         y: height as u32,
     };
 
-    let tilemap_entity = commands.spawn(Name::from("Tilemap")).id();
+    let tilemap_entity = commands
+        .spawn((Name::from("Tilemap"), StateScoped(AppState::Gameplay)))
+        .id();
     let mut tile_storage = TileStorage::empty(map_size);
 
     let mut vec = vec![];
     for x in 0..map_size.x {
         for y in 0..map_size.y {
-            let line = field::FIELD.lines().nth(y as usize).unwrap();
-            let char = line.chars().nth(x as usize).unwrap();
-            let index = match char {
+            let index = match layout[y as usize][x as usize] {
                 '#' => {
                     vec.push((
                         Vec3::new(x as f32 * 8.0, y as f32 * 8.0, 0.0),
@@ -79,7 +123,11 @@ pub fn spawn_field(glyph: Res<GlyphAsset>, mut commands: Commands) {
     let center = get_tilemap_center_transform(&map_size, &grid_size, &map_type, 0.0);
 
     commands
-        .spawn((Name::from("Walls"), Transform::default()))
+        .spawn((
+            Name::from("Walls"),
+            Transform::default(),
+            StateScoped(AppState::Gameplay),
+        ))
         .with_children(|parent| {
             for (translation, interactable, name) in vec {
                 parent.spawn((
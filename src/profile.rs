@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+
+use bevy::{
+    prelude::*,
+    scene::ron::{self, ser::PrettyConfig},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actors::{
+        CharacterClass,
+        formations::{Formation, Formations},
+    },
+    map::Pitch,
+    relics::{RELICS, Relic},
+    run::Run,
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Profile>()
+        .insert_resource(Profile::load())
+        .add_systems(
+            Update,
+            check_milestones.run_if(resource_added::<Formations>),
+        );
+}
+
+/// Where the profile is kept between sessions, a file next to the game or a key of the local storage on the web
+const PROFILE_PATH: &str = "profile.ron";
+/// Formations as profiles saved them before formations came from the data file,
+/// profiles saved since then wrap the name in parentheses, e.g. `Formation(("4-4-2"))`
+const LEGACY_FORMATIONS: [(&str, &str); 3] = [
+    ("Formation(FourThreeThree)", "Formation(\"4-3-3\")"),
+    ("Formation(FourFourTwo)", "Formation(\"4-4-2\")"),
+    ("Formation(ThreeFiveTwo)", "Formation(\"3-5-2\")"),
+];
+/// Runs the hall of fame remembers
+const HALL_OF_FAME_SIZE: usize = 10;
+
+/// Content that is locked until the profile reaches a milestone
+#[derive(Reflect, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Unlock {
+    Class(CharacterClass),
+    /// Name of a formation in the data file
+    Formation(Cow<'static, str>),
+    Pitch(Pitch),
+    Relic(Relic),
+}
+
+impl std::fmt::Display for Unlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unlock::Class(class) => write!(f, "CLASS: {}", class),
+            Unlock::Formation(formation) => write!(f, "FORMATION: {}", formation),
+            Unlock::Pitch(pitch) => write!(f, "PITCH: {}", pitch),
+            Unlock::Relic(relic) => write!(f, "RELIC: {}", relic),
+        }
+    }
+}
+
+/// Something to achieve over all runs
+enum Milestone {
+    WinMatch,
+    ScoreGoals(usize),
+    ReachRound(usize),
+    WinCup,
+    PlayRuns(usize),
+}
+
+impl Milestone {
+    fn reached(&self, profile: &Profile) -> bool {
+        match self {
+            Milestone::WinMatch => profile.matches_won > 0,
+            Milestone::ScoreGoals(goals) => profile.goals >= *goals,
+            Milestone::ReachRound(round) => profile.furthest >= *round,
+            Milestone::WinCup => profile.cups > 0,
+            Milestone::PlayRuns(runs) => profile.runs >= *runs,
+        }
+    }
+}
+
+/// Everything a milestone unlocks, content that isn't listed here is there from the start
+const MILESTONES: [(Milestone, Unlock); 6] = [
+    (Milestone::WinMatch, Unlock::Relic(Relic::TacticsBoard)),
+    (
+        Milestone::ReachRound(3),
+        Unlock::Formation(Cow::Borrowed("4-4-2")),
+    ),
+    (Milestone::ScoreGoals(10), Unlock::Pitch(Pitch::Park)),
+    (
+        Milestone::ReachRound(5),
+        Unlock::Class(CharacterClass::Winger),
+    ),
+    (Milestone::WinCup, Unlock::Relic(Relic::BribedReferee)),
+    (
+        Milestone::PlayRuns(3),
+        Unlock::Formation(Cow::Borrowed("3-5-2")),
+    ),
+];
+
+/// A finished run in the hall of fame
+#[derive(Reflect, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub seed: u64,
+    /// Name, class and level of every player that finished the run
+    pub squad: Vec<String>,
+    /// Round of the last match
+    pub reached: String,
+    pub won: bool,
+}
+
+/// Progress over all runs, stored in [`PROFILE_PATH`]
+#[derive(Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Profile {
+    unlocks: Vec<Unlock>,
+    /// Most recent run first
+    pub hall_of_fame: Vec<RunRecord>,
+    matches_won: usize,
+    goals: usize,
+    /// Most matches played in a single run
+    furthest: usize,
+    cups: usize,
+    runs: usize,
+    /// Unlocked by the last run
+    #[serde(skip)]
+    pub fresh: Vec<Unlock>,
+}
+
+impl Profile {
+    /// Reads the profile, a missing or broken save file starts a new one
+    pub fn load() -> Self {
+        let Some(text) = read_storage() else {
+            return Profile::default();
+        };
+        let text = migrate_formations(text);
+        ron::from_str(&text).unwrap_or_else(|error| {
+            warn!("Couldn't read the profile in {}: {}", PROFILE_PATH, error);
+            Profile::default()
+        })
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(write_storage);
+        if let Err(error) = result {
+            warn!("Couldn't save the profile to {}: {}", PROFILE_PATH, error);
+        }
+    }
+
+    pub fn is_unlocked(&self, unlock: &Unlock) -> bool {
+        self.unlocks.contains(unlock) || MILESTONES.iter().all(|(_, locked)| locked != unlock)
+    }
+
+    pub fn classes(&self) -> Vec<CharacterClass> {
+        [
+            CharacterClass::Goalkeeper,
+            CharacterClass::CentralDefender,
+            CharacterClass::Midfielder,
+            CharacterClass::Attacker,
+            CharacterClass::Winger,
+        ]
+        .into_iter()
+        .filter(|class| self.is_unlocked(&Unlock::Class(class.clone())))
+        .collect()
+    }
+
+    pub fn formations(&self, formations: &Formations) -> Vec<Formation> {
+        formations
+            .all()
+            .iter()
+            .filter(|formation| self.is_unlocked(&Unlock::Formation(formation.name.clone())))
+            .cloned()
+            .collect()
+    }
+
+    pub fn pitches(&self) -> Vec<Pitch> {
+        [Pitch::Stadium, Pitch::Park]
+            .into_iter()
+            .filter(|pitch| self.is_unlocked(&Unlock::Pitch(*pitch)))
+            .collect()
+    }
+
+    pub fn relics(&self) -> Vec<Relic> {
+        RELICS
+            .into_iter()
+            .filter(|relic| self.is_unlocked(&Unlock::Relic(*relic)))
+            .collect()
+    }
+
+    /// Adds a finished run to the hall of fame and unlocks every milestone it reached
+    fn record(&mut self, run: &Run) {
+        self.matches_won += run.results.iter().filter(|result| result.won()).count();
+        self.goals += run
+            .results
+            .iter()
+            .map(|result| result.score.0)
+            .sum::<usize>();
+        self.furthest = self.furthest.max(run.results.len());
+        self.cups += run.is_won() as usize;
+        self.runs += 1;
+        let reached = run
+            .results
+            .len()
+            .checked_sub(1)
+            .and_then(|round| run.ladder.get(round))
+            .map_or_else(String::new, |opponent| opponent.round.clone());
+        self.hall_of_fame.insert(
+            0,
+            RunRecord {
+                seed: run.seed,
+                squad: run
+                    .squad
+                    .iter()
+                    .map(|member| {
+                        format!(
+                            "{} ({}) LV {}",
+                            member.name,
+                            member.class,
+                            member.stats.level()
+                        )
+                    })
+                    .collect(),
+                reached,
+                won: run.is_won(),
+            },
+        );
+        self.hall_of_fame.truncate(HALL_OF_FAME_SIZE);
+
+        self.fresh = MILESTONES
+            .iter()
+            .filter(|(milestone, unlock)| !self.unlocks.contains(unlock) && milestone.reached(self))
+            .map(|(_, unlock)| unlock.clone())
+            .collect();
+        self.unlocks.extend(self.fresh.iter().cloned());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_storage() -> Option<String> {
+    std::fs::read_to_string(PROFILE_PATH).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_storage(text: String) -> Result<(), String> {
+    std::fs::write(PROFILE_PATH, text).map_err(|error| error.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or("there is no window")?
+        .local_storage()
+        .map_err(|error| format!("{:?}", error))?
+        .ok_or_else(|| "local storage is disabled".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_storage() -> Option<String> {
+    local_storage().ok()?.get_item(PROFILE_PATH).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_storage(text: String) -> Result<(), String> {
+    local_storage()?
+        .set_item(PROFILE_PATH, &text)
+        .map_err(|error| format!("{:?}", error))
+}
+
+/// Rewrites formation unlocks of older profiles to plain names
+fn migrate_formations(mut text: String) -> String {
+    for (legacy, formation) in LEGACY_FORMATIONS {
+        text = text.replace(legacy, formation);
+    }
+    const WRAPPED: &str = "Formation((\"";
+    while let Some(start) = text.find(WRAPPED) {
+        let Some(end) = text[start..].find("\"))").map(|end| start + end) else {
+            break;
+        };
+        text.replace_range(end..end + 3, "\")");
+        text.replace_range(start..start + WRAPPED.len(), "Formation(\"");
+    }
+    text
+}
+
+/// Milestones can only unlock formations that are in the data file
+fn check_milestones(formations: Res<Formations>) {
+    for (_, unlock) in &MILESTONES {
+        match unlock {
+            Unlock::Formation(name) if !formations.contains(name) => error!(
+                "A milestone unlocks the formation {}, which isn't in the data file",
+                name
+            ),
+            _ => (),
+        }
+    }
+}
+
+/// Saves the profile once a run is over
+pub fn record_run(run: Res<Run>, mut profile: ResMut<Profile>) {
+    if !run.is_over() {
+        return;
+    }
+    profile.record(run.as_ref());
+    profile.save();
+}
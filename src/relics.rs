@@ -0,0 +1,229 @@
+use bevy::{color::palettes::css::GOLD, prelude::*};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppSet, FontAsset,
+    actors::{self, Stats, Team, physics::BallPhysics},
+    conditions::MatchCondition,
+    run::Run,
+    states::{AppState, gameplay::RelicStrip},
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Relic>()
+        .register_type::<Modifiers>()
+        .init_resource::<Modifiers>()
+        .add_systems(
+            OnEnter(AppState::Gameplay),
+            apply_modifiers.before(actors::startup),
+        )
+        .add_systems(
+            Update,
+            show_relics
+                .in_set(AppSet::Update)
+                .run_if(in_state(AppState::Gameplay).and(resource_changed::<Modifiers>)),
+        );
+}
+
+/// An item that helps our team for the rest of the run
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Relic {
+    HeavyBall,
+    LuckyBoots,
+    TacticsBoard,
+    BribedReferee,
+}
+
+/// Every relic the shop can offer
+pub const RELICS: [Relic; 4] = [
+    Relic::HeavyBall,
+    Relic::LuckyBoots,
+    Relic::TacticsBoard,
+    Relic::BribedReferee,
+];
+
+impl Relic {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Relic::HeavyBall => "Heavy Ball",
+            Relic::LuckyBoots => "Lucky Boots",
+            Relic::TacticsBoard => "Tactics Board",
+            Relic::BribedReferee => "Bribed Referee",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Relic::HeavyBall => "The ball keeps its speed for longer",
+            Relic::LuckyBoots => "Our players ignore the zones of control of opponents",
+            Relic::TacticsBoard => "Our players start every round with an extra AP",
+            Relic::BribedReferee => "The referee adds stoppage time while we are behind",
+        }
+    }
+
+    /// Coins the relic costs in the shop
+    pub fn price(&self) -> usize {
+        match self {
+            Relic::HeavyBall => 20,
+            Relic::LuckyBoots => 25,
+            Relic::TacticsBoard => 30,
+            Relic::BribedReferee => 20,
+        }
+    }
+
+    /// Adds the effect of the relic to the modifiers of a match
+    fn register(&self, modifiers: &mut Modifiers) {
+        match self {
+            Relic::HeavyBall => modifiers.ball_friction *= 0.75,
+            Relic::LuckyBoots => modifiers.ignore_zones = true,
+            Relic::TacticsBoard => modifiers.bonus_ap += 1,
+            Relic::BribedReferee => modifiers.stoppage_time += 5,
+        }
+    }
+}
+
+impl std::fmt::Display for Relic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Registry of the relics our team brings to the current match, the conditions of the match and what they add up to.
+///
+/// The kick, pathfinding and turn systems read their modifiers from here instead of checking relics or conditions.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct Modifiers {
+    pub relics: Vec<Relic>,
+    pub conditions: Vec<MatchCondition>,
+    /// Multiplies the friction of the ball
+    pub ball_friction: f32,
+    /// Acceleration of a ball in the air in tiles per second squared
+    pub wind: Vec2,
+    /// Steps earlier every runner starts to sprint
+    pub early_sprint: usize,
+    /// Our players walk past opponents as if they had no zones of control
+    pub ignore_zones: bool,
+    /// AP our players get on top at the start of every round
+    pub bonus_ap: usize,
+    /// Most initiative our players lose at random whenever the next player is picked
+    pub initiative_penalty: u8,
+    /// Turns the match goes on for while we are behind
+    pub stoppage_time: usize,
+    /// Turns the match goes on for while the opponent is behind
+    pub home_stoppage_time: usize,
+    /// A second ball is in play
+    pub second_ball: bool,
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Modifiers {
+            relics: vec![],
+            conditions: vec![],
+            ball_friction: 1.0,
+            wind: Vec2::ZERO,
+            early_sprint: 0,
+            ignore_zones: false,
+            bonus_ap: 0,
+            initiative_penalty: 0,
+            stoppage_time: 0,
+            home_stoppage_time: 0,
+            second_ball: false,
+        }
+    }
+}
+
+impl Modifiers {
+    pub fn new(relics: &[Relic], conditions: &[MatchCondition]) -> Self {
+        let mut modifiers = Modifiers {
+            relics: relics.to_vec(),
+            conditions: conditions.to_vec(),
+            ..default()
+        };
+        for relic in relics {
+            relic.register(&mut modifiers);
+        }
+        for condition in conditions {
+            condition.register(&mut modifiers);
+        }
+        modifiers
+    }
+
+    /// Team whose zones of control a player respects while finding a path
+    pub fn zone_team(&self, stats: &Stats, team: Team) -> Option<Team> {
+        if team == Team::Player && self.ignore_zones {
+            return None;
+        }
+        stats.zone_team(team)
+    }
+
+    /// AP a player of `team` gets on top at the start of a round
+    pub fn bonus_ap(&self, team: Team) -> usize {
+        match team {
+            Team::Player => self.bonus_ap,
+            Team::Enemy => 0,
+        }
+    }
+
+    /// Initiative a player of `team` has when the next player is picked
+    pub fn initiative(&self, team: Team, initiative: u8, sampler: &mut ChaCha8Rng) -> u8 {
+        match team {
+            Team::Player if self.initiative_penalty > 0 => {
+                initiative.saturating_sub(sampler.random_range(0..=self.initiative_penalty))
+            }
+            _ => initiative,
+        }
+    }
+
+    /// Turns the match goes on for with the given score of our team and the opponent
+    pub fn stoppage_time(&self, score: (usize, usize)) -> usize {
+        match score.0.cmp(&score.1) {
+            std::cmp::Ordering::Less => self.stoppage_time,
+            std::cmp::Ordering::Greater => self.home_stoppage_time,
+            std::cmp::Ordering::Equal => 0,
+        }
+    }
+}
+
+fn apply_modifiers(
+    run: Res<Run>,
+    mut modifiers: ResMut<Modifiers>,
+    mut physics: ResMut<BallPhysics>,
+) {
+    let conditions = run
+        .opponent()
+        .map_or_else(Vec::new, |opponent| opponent.conditions.clone());
+    *modifiers = Modifiers::new(&run.relics, &conditions);
+    physics.friction = BallPhysics::default().friction * modifiers.ball_friction;
+    physics.wind = modifiers.wind;
+}
+
+fn show_relics(
+    modifiers: Res<Modifiers>,
+    font_asset: Res<FontAsset>,
+    strip: Single<Entity, With<RelicStrip>>,
+    mut commands: Commands,
+) {
+    let relics = if modifiers.relics.is_empty() {
+        "NO RELICS".to_string()
+    } else {
+        let labels: Vec<_> = modifiers.relics.iter().map(Relic::label).collect();
+        format!("RELICS: {}", labels.join(" | "))
+    };
+    commands
+        .entity(*strip)
+        .despawn_descendants()
+        .with_children(|strip| {
+            strip.spawn((
+                Text::new(relics),
+                TextFont {
+                    font: font_asset.font.clone_weak(),
+                    ..default()
+                },
+                TextColor(GOLD.into()),
+            ));
+        });
+}
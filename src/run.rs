@@ -0,0 +1,658 @@
+use std::collections::HashMap;
+
+use bevy::{
+    color::palettes::css::{CRIMSON, DARK_CYAN, HOT_PINK, PURPLE, ROYAL_BLUE, SKY_BLUE},
+    prelude::*,
+};
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    AppSet, FontAsset,
+    actors::{
+        self, Ball, CharacterClass, SECOND_BALL_SPOT, Sampler, SecondBall, Stats, Team,
+        abilities::Threaded,
+        actions::{Claimed, ClaimedBy, InHands, Kicked},
+        aerial::HeaderChance,
+        enemy::{STYLES, TacticalStyle},
+        experience::{ASSIST_XP, Experience, GOAL_XP, LastTouch},
+        formations::{FORMATION_SIZE, Formation, Formations, KickoffSpot},
+        physics::{Airborne, Spin},
+        random_name,
+        substitutions::{BENCH, Bench, spawn_benches},
+        tactics::{Instructions, Role},
+    },
+    conditions::MatchCondition,
+    map::Pitch,
+    profile::Profile,
+    relics::{Modifiers, Relic},
+    states::{AppState, GameplayStates, gameplay::Header},
+    ui::LogEvent,
+};
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<Run>()
+        .register_type::<CurrentMatch>()
+        .init_resource::<CurrentMatch>()
+        .add_systems(OnExit(AppState::Loading), start_run)
+        .add_observer(count_goal)
+        .add_systems(
+            OnEnter(AppState::Gameplay),
+            start_match.before(actors::startup).before(spawn_benches),
+        )
+        .add_systems(OnEnter(GameplayStates::Banner(Team::Player)), tick_clock)
+        .add_systems(OnEnter(GameplayStates::Banner(Team::Enemy)), tick_clock)
+        .add_systems(
+            Update,
+            update_scoreboard
+                .in_set(AppSet::Update)
+                .run_if(in_state(AppState::Gameplay).and(resource_changed::<CurrentMatch>)),
+        );
+}
+
+/// Turns both teams get together in a match
+const MATCH_LENGTH: usize = 90;
+/// Rounds of the cup and how strong the opponents are in each of them
+const ROUNDS: [(&str, f32); 5] = [
+    ("ROUND OF 32", 0.8),
+    ("ROUND OF 16", 0.9),
+    ("QUARTER FINAL", 1.0),
+    ("SEMI FINAL", 1.1),
+    ("FINAL", 1.25),
+];
+const CLUB_SUFFIXES: [&str; 6] = ["UNITED", "CITY", "ROVERS", "ATHLETIC", "WANDERERS", "TOWN"];
+/// Shirts of opposing clubs, none of them looks like ours, the highlight of the current player or the white ball
+const CLUB_COLORS: [Srgba; 6] = [DARK_CYAN, CRIMSON, ROYAL_BLUE, PURPLE, SKY_BLUE, HOT_PINK];
+/// Penalties each team takes before sudden death
+const PENALTIES: usize = 5;
+/// Sudden death ends in a coin toss after this many penalties
+const MAX_PENALTIES: usize = 20;
+/// Most players a squad can have, a full lineup and a full bench
+pub const ROSTER_CAP: usize = FORMATION_SIZE + BENCH.len();
+/// Players offered in every draft
+const DRAFT_SIZE: usize = 3;
+/// Relics offered in every shop
+const SHOP_SIZE: usize = 3;
+/// Coins for playing a match, every goal and a win
+const MATCH_COINS: usize = 10;
+const GOAL_COINS: usize = 5;
+const WIN_COINS: usize = 10;
+
+/// A player of our squad between matches
+#[derive(Reflect, Clone)]
+pub struct Member {
+    pub name: String,
+    pub stats: Stats,
+    pub class: CharacterClass,
+    /// Anchor of the formation the player starts on, picked on the pre-match screen or kept from the last match
+    pub slot: Option<usize>,
+    pub role: Role,
+}
+
+/// A generated club the run meets in one of the rounds
+#[derive(Reflect, Clone)]
+pub struct Opponent {
+    pub name: String,
+    pub round: String,
+    /// Multiplies the stats of every opposing player
+    pub strength: f32,
+    pub color: Color,
+    pub formation: Formation,
+    pub style: TacticalStyle,
+    /// Name of the best player of the club
+    pub star: String,
+    /// Index into the anchors of the formation the star plays on
+    pub star_position: usize,
+    /// Rolled for the match against the club
+    pub conditions: Vec<MatchCondition>,
+}
+
+impl Opponent {
+    fn generate(
+        round: &str,
+        strength: f32,
+        formations: &Formations,
+        sampler: &mut ChaCha8Rng,
+    ) -> Self {
+        let name = random_name(sampler);
+        let town = name.rsplit(' ').next().unwrap_or_default();
+        let suffix = CLUB_SUFFIXES[sampler.random_range(0..CLUB_SUFFIXES.len())];
+        let formations = formations.all();
+        let formation = formations[sampler.random_range(0..formations.len())].clone();
+        Opponent {
+            name: format!("{} {}", town, suffix),
+            round: round.to_string(),
+            strength,
+            color: CLUB_COLORS[sampler.random_range(0..CLUB_COLORS.len())].into(),
+            style: STYLES[sampler.random_range(0..STYLES.len())],
+            star: random_name(sampler),
+            // keepers are never the star
+            star_position: sampler.random_range(1..formation.anchors().len()),
+            formation,
+            conditions: MatchCondition::roll(sampler),
+        }
+    }
+}
+
+#[derive(Reflect, Clone)]
+pub struct MatchResult {
+    pub opponent: String,
+    /// Goals of our team and of the opponent
+    pub score: (usize, usize),
+    /// Shootout after a draw, in the same order as the score
+    pub penalties: Option<(usize, usize)>,
+}
+
+impl MatchResult {
+    pub fn won(&self) -> bool {
+        let (ours, theirs) = self.penalties.unwrap_or(self.score);
+        ours > theirs
+    }
+}
+
+impl std::fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} - {} {}",
+            if self.won() { "WON" } else { "LOST" },
+            self.score.0,
+            self.score.1,
+            self.opponent
+        )?;
+        if let Some((ours, theirs)) = self.penalties {
+            write!(f, " ({} - {} on penalties)", ours, theirs)?;
+        }
+        Ok(())
+    }
+}
+
+/// Who plays and who watches in the next match
+pub struct Lineup<'a> {
+    /// Index into the anchors of the formation and the player taking it
+    pub starters: Vec<(usize, &'a Member)>,
+    pub bench: Vec<&'a Member>,
+    /// Sidelined players and everyone that didn't make the squad
+    pub reserves: Vec<&'a Member>,
+}
+
+/// A cup run, our squad plays a ladder of seeded matches until it loses or wins the final
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct Run {
+    pub seed: u64,
+    pub squad: Vec<Member>,
+    pub ladder: Vec<Opponent>,
+    pub results: Vec<MatchResult>,
+    /// Earned in matches, spent in the shop
+    pub coins: usize,
+    pub relics: Vec<Relic>,
+    pub formation: Formation,
+    /// How our team plays off the ball
+    pub instructions: Instructions,
+    pub pitch: Pitch,
+}
+
+impl Run {
+    /// Starts a run with a squad for the first formation on a pitch the profile unlocked
+    pub fn new(seed: u64, profile: &Profile, formations: &Formations) -> Self {
+        let mut sampler = ChaCha8Rng::seed_from_u64(seed);
+        let formation = formations.first().clone();
+        let pitches = profile.pitches();
+        let pitch = pitches[sampler.random_range(0..pitches.len())];
+        let classes = formation
+            .anchors()
+            .iter()
+            .map(|anchor| &anchor.class)
+            .chain(BENCH.iter());
+        let squad = classes
+            .enumerate()
+            .map(|(index, class)| Member {
+                name: random_name(&mut sampler),
+                stats: Stats::from_class(class, index, &mut sampler),
+                class: class.clone(),
+                slot: None,
+                role: Role::for_class(class),
+            })
+            .collect();
+        let ladder = ROUNDS
+            .iter()
+            .map(|(round, strength)| Opponent::generate(round, *strength, formations, &mut sampler))
+            .collect();
+        Run {
+            seed,
+            squad,
+            ladder,
+            results: vec![],
+            coins: 0,
+            relics: vec![],
+            formation,
+            instructions: Instructions::default(),
+            pitch,
+        }
+    }
+
+    pub fn opponent(&self) -> Option<&Opponent> {
+        self.ladder.get(self.results.len())
+    }
+
+    /// Every match of a run gets its own seed
+    pub fn match_seed(&self) -> u64 {
+        self.seed.wrapping_add(self.results.len() as u64)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.opponent().is_none() || self.results.last().is_some_and(|result| !result.won())
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.opponent().is_none() && self.results.iter().all(MatchResult::won)
+    }
+
+    /// Generates the players offered before the next match, they get better the deeper the run goes
+    pub fn draft(&self, profile: &Profile) -> Vec<Member> {
+        let mut sampler = ChaCha8Rng::seed_from_u64(self.match_seed().rotate_left(32));
+        let strength = self.opponent().map_or(1.0, |opponent| opponent.strength);
+        let classes = profile.classes();
+        (0..DRAFT_SIZE)
+            .map(|index| {
+                let class = &classes[sampler.random_range(0..classes.len())];
+                let mut stats = Stats::from_class(class, index, &mut sampler);
+                stats.strengthen(strength);
+                Member {
+                    name: random_name(&mut sampler),
+                    stats,
+                    class: class.clone(),
+                    slot: None,
+                    role: Role::for_class(class),
+                }
+            })
+            .collect()
+    }
+
+    /// Relics the profile unlocked that are offered before the next match, the run never sees a relic it already owns
+    pub fn shop(&self, profile: &Profile) -> Vec<Relic> {
+        let mut sampler = ChaCha8Rng::seed_from_u64(self.match_seed().rotate_left(16));
+        let mut offers: Vec<Relic> = profile
+            .relics()
+            .into_iter()
+            .filter(|relic| !self.relics.contains(relic))
+            .collect();
+        offers.shuffle(&mut sampler);
+        offers.truncate(SHOP_SIZE);
+        offers
+    }
+
+    /// Pays for a relic, returns false if the run can't afford it or already owns it
+    pub fn buy(&mut self, relic: Relic) -> bool {
+        if self.coins < relic.price() || self.relics.contains(&relic) {
+            return false;
+        }
+        self.coins -= relic.price();
+        self.relics.push(relic);
+        true
+    }
+
+    /// Adds a player to the squad, returns false if the squad is full
+    pub fn sign(&mut self, member: Member) -> bool {
+        if self.squad.len() >= ROSTER_CAP {
+            return false;
+        }
+        self.squad.push(member);
+        true
+    }
+
+    /// Releases a player from the squad, a full lineup always stays
+    pub fn release(&mut self, index: usize) -> Option<Member> {
+        if self.squad.len() <= FORMATION_SIZE || index >= self.squad.len() {
+            return None;
+        }
+        Some(self.squad.remove(index))
+    }
+
+    /// Switches the formation, every player goes back to being picked by class
+    pub fn set_formation(&mut self, formation: Formation) {
+        self.formation = formation;
+        for member in &mut self.squad {
+            member.slot = None;
+        }
+    }
+
+    /// Starts a squad member on an anchor of the formation, whoever started there takes their old place.
+    ///
+    /// Returns false if the player is sidelined.
+    pub fn assign(&mut self, member: usize, slot: usize) -> bool {
+        if self
+            .squad
+            .get(member)
+            .is_none_or(|member| member.stats.is_sidelined())
+        {
+            return false;
+        }
+        // pin the current lineup so the players nobody moved stay where they are
+        let starters = self.lineup().starters;
+        let slots: Vec<_> = self
+            .squad
+            .iter()
+            .map(|candidate| {
+                starters
+                    .iter()
+                    .find(|(_, starter)| std::ptr::eq(*starter, candidate))
+                    .map(|(index, _)| *index)
+            })
+            .collect();
+        let previous = slots[member];
+        for (candidate, slot) in self.squad.iter_mut().zip(slots) {
+            candidate.slot = slot;
+        }
+        if let Some(other) = self.squad.iter_mut().find(|other| other.slot == Some(slot)) {
+            other.slot = previous;
+        }
+        self.squad[member].slot = Some(slot);
+        true
+    }
+
+    /// Index into the squad of the player starting on an anchor
+    pub fn starter(&self, slot: usize) -> Option<usize> {
+        let starters = self.lineup().starters;
+        let (_, starter) = starters.iter().find(|(index, _)| *index == slot)?;
+        self.squad
+            .iter()
+            .position(|member| std::ptr::eq(member, *starter))
+    }
+
+    /// Puts players on the anchors they were assigned to and fills every other anchor with a player of the same class if possible, the next seven go on the bench
+    pub fn lineup(&self) -> Lineup<'_> {
+        let (mut available, mut reserves): (Vec<&Member>, Vec<&Member>) = self
+            .squad
+            .iter()
+            .partition(|member| !member.stats.is_sidelined());
+        let mut starters: Vec<(usize, &Member)> = vec![];
+        for index in 0..FORMATION_SIZE {
+            if let Some(pick) = available
+                .iter()
+                .position(|member| member.slot == Some(index))
+            {
+                starters.push((index, available.remove(pick)));
+            }
+        }
+        for (index, anchor) in self.formation.anchors().iter().enumerate() {
+            if starters.iter().any(|(taken, _)| *taken == index) {
+                continue;
+            }
+            let pick = available
+                .iter()
+                .position(|member| member.class == anchor.class)
+                .or((!available.is_empty()).then_some(0));
+            if let Some(pick) = pick {
+                starters.push((index, available.remove(pick)));
+            }
+        }
+        starters.sort_by_key(|(index, _)| *index);
+        let bench = available.len().min(BENCH.len());
+        reserves.extend(available.drain(bench..));
+        Lineup {
+            starters,
+            bench: available,
+            reserves,
+        }
+    }
+}
+
+/// Score and clock of the match that is being played
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct CurrentMatch {
+    pub goals: HashMap<Team, usize>,
+    pub turn: usize,
+}
+
+impl CurrentMatch {
+    fn score(&self) -> (usize, usize) {
+        let goals = |team| self.goals.get(&team).copied().unwrap_or(0);
+        (goals(Team::Player), goals(Team::Enemy))
+    }
+}
+
+/// Triggered when a ball crosses the goal line
+#[derive(Event)]
+pub struct GoalScored {
+    pub by: Team,
+    pub ball: Entity,
+}
+
+/// Seeds the match and clears the scoreboard
+/// The first run starts once the formations are loaded
+fn start_run(profile: Res<Profile>, formations: Res<Formations>, mut commands: Commands) {
+    commands.insert_resource(Run::new(rand::random(), &profile, &formations));
+}
+
+fn start_match(run: Res<Run>, mut sampler: ResMut<Sampler>, mut current: ResMut<CurrentMatch>) {
+    *sampler = Sampler::seeded(run.match_seed());
+    *current = CurrentMatch::default();
+}
+
+type Kickoff<'a> = (
+    Entity,
+    &'a Team,
+    Has<Claimed>,
+    Option<(&'a mut Transform, &'a KickoffSpot)>,
+);
+
+type KickoffBall<'a> = (
+    Entity,
+    &'a mut Transform,
+    Option<&'a LastTouch>,
+    Has<SecondBall>,
+);
+
+/// Counts the goal and lines both teams up again with the balls on their spots, the team that conceded kicks off
+fn count_goal(
+    trigger: Trigger<GoalScored>,
+    mut current: ResMut<CurrentMatch>,
+    mut balls: Query<KickoffBall, With<Ball>>,
+    mut players: Query<Kickoff, Without<Ball>>,
+    mut next: ResMut<NextState<GameplayStates>>,
+    mut events: EventWriter<LogEvent>,
+    mut commands: Commands,
+) {
+    let GoalScored { by, ball } = *trigger.event();
+    // only a ball crossing the line counts
+    let Ok((_, _, last_touch, _)) = balls.get(ball) else {
+        return;
+    };
+    *current.goals.entry(by).or_default() += 1;
+    let (ours, theirs) = current.score();
+    events.send(LogEvent(format!("GOAL! It's {} - {}", ours, theirs)));
+
+    // own goals earn nobody anything
+    let scorer = last_touch.filter(|last| {
+        players
+            .get(last.player)
+            .is_ok_and(|(_, team, ..)| *team == by)
+    });
+    if let Some(LastTouch { player, previous }) = scorer {
+        commands.trigger_targets(Experience(GOAL_XP), *player);
+        if let Some(assist) = previous {
+            commands.trigger_targets(Experience(ASSIST_XP), *assist);
+        }
+    }
+
+    for (entity, mut transform, _, second) in &mut balls {
+        let spot = if second { SECOND_BALL_SPOT } else { Vec2::ZERO };
+        transform.translation = spot.extend(transform.translation.z);
+        commands
+            .entity(entity)
+            .remove::<(Kicked, Spin, Airborne, Threaded, ClaimedBy, LastTouch)>();
+    }
+    // everyone on the field lines up again for the kickoff
+    for (player, _, claimed, spot) in &mut players {
+        if claimed {
+            commands.entity(player).remove::<(Claimed, InHands)>();
+        }
+        commands.entity(player).remove::<HeaderChance>();
+        if let Some((mut transform, spot)) = spot {
+            transform.translation = spot.extend(transform.translation.z);
+        }
+    }
+    next.set(GameplayStates::Banner(by.opponent()));
+}
+
+type Player<'a> = (
+    &'a Name,
+    &'a Stats,
+    &'a CharacterClass,
+    &'a Team,
+    Option<&'a Bench>,
+    &'a Role,
+);
+
+/// Ends the match once its time is up, a draw goes to penalties.
+///
+/// A bribed referee keeps the match going for a while when we are behind, a biased one when our opponents are.
+fn tick_clock(
+    mut current: ResMut<CurrentMatch>,
+    mut run: ResMut<Run>,
+    modifiers: Res<Modifiers>,
+    mut sampler: ResMut<Sampler>,
+    players: Query<Player>,
+    mut events: EventWriter<LogEvent>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    current.turn += 1;
+    if current.turn < MATCH_LENGTH {
+        return;
+    }
+    let score = current.score();
+    let stoppage_time = modifiers.stoppage_time(score);
+    if current.turn < MATCH_LENGTH + stoppage_time {
+        if current.turn == MATCH_LENGTH {
+            events.send(LogEvent(format!(
+                "The referee adds {} minutes of stoppage time",
+                stoppage_time
+            )));
+        }
+        return;
+    }
+    let Some(opponent) = run.opponent().cloned() else {
+        return;
+    };
+    let penalties = (score.0 == score.1).then(|| penalty_shootout(&players, &mut sampler.0));
+
+    // everyone that played goes back into the squad, the bench after the starters
+    let mut fielded = players
+        .iter()
+        .filter(|(_, _, _, team, ..)| **team == Team::Player)
+        .collect::<Vec<_>>();
+    fielded.sort_by_key(|(_, stats, _, _, bench, _)| (bench.is_some(), stats.initiative));
+    let mut squad = fielded
+        .into_iter()
+        .map(|(name, stats, class, _, bench, role)| {
+            let mut stats = stats.clone();
+            stats.recover();
+            Member {
+                name: name.to_string(),
+                slot: bench.is_none().then_some(stats.initiative as usize),
+                stats,
+                class: class.clone(),
+                role: *role,
+            }
+        })
+        .collect::<Vec<_>>();
+    squad.extend(
+        run.lineup()
+            .reserves
+            .into_iter()
+            .cloned()
+            .map(|mut member| {
+                member.stats.miss_match();
+                member.slot = None;
+                member
+            }),
+    );
+    run.squad = squad;
+    let result = MatchResult {
+        opponent: opponent.name,
+        score,
+        penalties,
+    };
+    run.coins += MATCH_COINS + score.0 * GOAL_COINS + if result.won() { WIN_COINS } else { 0 };
+    run.results.push(result);
+    next.set(AppState::Summary);
+}
+
+/// Five penalties each and sudden death after that, the hardest kickers go first
+fn penalty_shootout(players: &Query<Player>, sampler: &mut ChaCha8Rng) -> (usize, usize) {
+    let takers = |team: Team| {
+        let mut kickers = players
+            .iter()
+            .filter(|(_, _, _, other, bench, _)| **other == team && bench.is_none())
+            .map(|(_, stats, ..)| stats.kick_strength())
+            .collect::<Vec<_>>();
+        kickers.sort_by(|a, b| b.total_cmp(a));
+        let keeper = players
+            .iter()
+            .find(|(_, _, class, other, bench, _)| {
+                **other == team.opponent()
+                    && bench.is_none()
+                    && matches!(class, CharacterClass::Goalkeeper)
+            })
+            .map_or(0.0, |(_, stats, ..)| stats.defense());
+        (kickers, keeper)
+    };
+    let shoot = |(kickers, keeper): &(Vec<f32>, f32), round: usize, sampler: &mut ChaCha8Rng| {
+        let Some(strength) = kickers.get(round % kickers.len().max(1)) else {
+            return false;
+        };
+        let chance = (0.75 + (strength - 20.0) / 100.0 - keeper * 0.3).clamp(0.2, 0.95);
+        sampler.random_range(0.0..=1.0) < chance
+    };
+    let (ours, theirs) = (takers(Team::Player), takers(Team::Enemy));
+    let mut score = (0, 0);
+    for round in 0..MAX_PENALTIES {
+        score.0 += shoot(&ours, round, sampler) as usize;
+        score.1 += shoot(&theirs, round, sampler) as usize;
+        if round + 1 >= PENALTIES && score.0 != score.1 {
+            return score;
+        }
+    }
+    if sampler.random_bool(0.5) {
+        score.0 += 1;
+    } else {
+        score.1 += 1;
+    }
+    score
+}
+
+fn update_scoreboard(
+    run: Res<Run>,
+    current: Res<CurrentMatch>,
+    font_asset: Res<FontAsset>,
+    header: Single<Entity, With<Header>>,
+    mut commands: Commands,
+) {
+    let Some(opponent) = run.opponent() else {
+        return;
+    };
+    let (ours, theirs) = current.score();
+    // stoppage time counts on from the full ninety minutes
+    let clock = match current.turn.checked_sub(MATCH_LENGTH) {
+        Some(stoppage) if stoppage > 0 => format!("{}+{}", MATCH_LENGTH, stoppage),
+        _ => current.turn.to_string(),
+    };
+    commands
+        .entity(*header)
+        .despawn_descendants()
+        .with_children(|header| {
+            header.spawn((
+                Text::new(format!(
+                    "{} | YOU {} - {} {} | {}'",
+                    opponent.round, ours, theirs, opponent.name, clock
+                )),
+                TextFont {
+                    font: font_asset.font.clone_weak(),
+                    ..default()
+                },
+            ));
+        });
+}
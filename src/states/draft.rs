@@ -0,0 +1,237 @@
+use bevy::{
+    color::palettes::css::{GREY, YELLOW},
+    prelude::*,
+};
+
+use crate::{
+    AppSet, FontAsset,
+    actors::{experience::Improvement, traits::Trait},
+    profile::Profile,
+    run::{Member, ROSTER_CAP, Run},
+    theme::prelude::*,
+};
+
+use super::AppState;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Draft>()
+        .add_systems(OnEnter(AppState::Draft), start_draft)
+        .add_systems(
+            Update,
+            (
+                handle_input.in_set(AppSet::RecordInput),
+                show_draft
+                    .in_set(AppSet::Update)
+                    .run_if(resource_changed::<Draft>.or(resource_changed::<Run>)),
+            )
+                .run_if(in_state(AppState::Draft)),
+        );
+}
+
+/// Players on offer and the squad member that is selected for release
+#[derive(Resource, Default)]
+struct Draft {
+    candidates: Vec<Member>,
+    cursor: usize,
+    message: String,
+}
+
+#[derive(Component)]
+struct DraftScreen;
+
+fn start_draft(
+    run: Res<Run>,
+    profile: Res<Profile>,
+    mut draft: ResMut<Draft>,
+    mut commands: Commands,
+) {
+    *draft = Draft {
+        candidates: run.draft(&profile),
+        cursor: 0,
+        message: format!("Pick one player, the squad holds {} at most", ROSTER_CAP),
+    };
+    commands.ui_root().insert((
+        Name::new("Draft"),
+        StateScoped(AppState::Draft),
+        DraftScreen,
+    ));
+}
+
+/// Keys that spend a level up of the selected squad member
+const IMPROVEMENTS: [(KeyCode, Improvement); 11] = [
+    (KeyCode::KeyK, Improvement::KickStrength),
+    (KeyCode::KeyP, Improvement::Passing),
+    (KeyCode::KeyD, Improvement::Defense),
+    (KeyCode::KeyB, Improvement::Dribbling),
+    (KeyCode::KeyH, Improvement::Heading),
+    (KeyCode::KeyA, Improvement::Ap),
+    (KeyCode::KeyM, Improvement::Mastery),
+    (KeyCode::KeyL, Improvement::Learn(Trait::LongPasser)),
+    (KeyCode::KeyI, Improvement::Learn(Trait::IronWall)),
+    (KeyCode::KeyS, Improvement::Learn(Trait::Showboat)),
+    (KeyCode::KeyE, Improvement::Learn(Trait::Elusive)),
+];
+
+/// 1-3 signs a player, UP and DOWN select a squad member, X releases them and ENTER goes on to the pre-match screen.
+///
+/// Squad members with level ups improve with the keys in [`IMPROVEMENTS`].
+fn handle_input(
+    input: Res<ButtonInput<KeyCode>>,
+    mut draft: ResMut<Draft>,
+    mut run: ResMut<Run>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    let picks = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+    let pick = picks
+        .iter()
+        .position(|key| input.just_pressed(*key))
+        .and_then(|pick| draft.candidates.get(pick).cloned());
+    if let Some(member) = pick {
+        let name = member.name.clone();
+        draft.message = if run.sign(member) {
+            draft.candidates.clear();
+            format!("{} signs for the club", name)
+        } else {
+            "The squad is full, release someone first".to_string()
+        };
+    }
+    if input.just_pressed(KeyCode::ArrowUp) {
+        draft.cursor = draft.cursor.saturating_sub(1);
+    }
+    if input.just_pressed(KeyCode::ArrowDown) {
+        draft.cursor = (draft.cursor + 1).min(run.squad.len().saturating_sub(1));
+    }
+    if input.just_pressed(KeyCode::KeyX) {
+        let cursor = draft.cursor;
+        draft.message = match run.release(cursor) {
+            Some(member) => format!("{} leaves the club", member.name),
+            None => "Nobody else can leave, the squad needs eleven players".to_string(),
+        };
+        draft.cursor = cursor.min(run.squad.len().saturating_sub(1));
+    }
+    let improvement = IMPROVEMENTS
+        .iter()
+        .find(|(key, _)| input.just_pressed(*key))
+        .map(|(_, improvement)| *improvement);
+    if let Some(improvement) = improvement {
+        let cursor = draft.cursor;
+        if let Some(member) = run.squad.get_mut(cursor) {
+            let improved = member.stats.improve(improvement);
+            draft.message = match (improvement, improved) {
+                (Improvement::Learn(quirk), true) => {
+                    format!("{} picks up the {} trait", member.name, quirk)
+                }
+                (Improvement::Learn(quirk), false) => {
+                    format!("{} can't pick up the {} trait", member.name, quirk)
+                }
+                (_, true) => format!("{} improves their {}", member.name, improvement.label()),
+                (_, false) => format!(
+                    "{} can't improve their {}",
+                    member.name,
+                    improvement.label()
+                ),
+            };
+        }
+    }
+    if input.just_pressed(KeyCode::Enter) {
+        next.set(AppState::PreMatch);
+    }
+}
+
+fn show_draft(
+    run: Res<Run>,
+    draft: Res<Draft>,
+    font_asset: Res<FontAsset>,
+    screen: Single<Entity, With<DraftScreen>>,
+    mut commands: Commands,
+) {
+    let text = |text: String, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: font_asset.font.clone_weak(),
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+    commands
+        .entity(*screen)
+        .despawn_descendants()
+        .with_children(|root| {
+            root.label(format!(
+                "DRAFT - {}/{} players - {} - {}",
+                run.squad.len(),
+                ROSTER_CAP,
+                run.formation,
+                run.pitch
+            ));
+            root.spawn(Node {
+                column_gap: Val::Px(32.0),
+                ..default()
+            })
+            .with_children(|columns| {
+                columns
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    })
+                    .with_children(|squad| {
+                        for (index, member) in run.squad.iter().enumerate() {
+                            let color = if index == draft.cursor {
+                                YELLOW.into()
+                            } else if member.stats.is_sidelined() {
+                                GREY.into()
+                            } else {
+                                Color::WHITE
+                            };
+                            let level_ups = if member.stats.level_ups() > 0 {
+                                " +"
+                            } else {
+                                ""
+                            };
+                            squad.spawn(text(
+                                format!(
+                                    "{} ({}) LV {}{}",
+                                    member.name,
+                                    member.class,
+                                    member.stats.level(),
+                                    level_ups
+                                ),
+                                color,
+                            ));
+                        }
+                    });
+                columns
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    })
+                    .with_children(|candidates| {
+                        if let Some(member) = run.squad.get(draft.cursor) {
+                            candidates.spawn(text(member.name.clone(), YELLOW.into()));
+                            candidates.spawn(text(member.stats.to_string(), Color::WHITE));
+                            if member.stats.level_ups() > 0 {
+                                candidates.spawn(text(
+                                    "LEVEL UP: K kick | P passing | D defense | B dribbling\nH heading | A AP | M ability mastery\nTRAIT: L Long Passer | I Iron Wall | S Showboat | E Elusive".to_string(),
+                                    YELLOW.into(),
+                                ));
+                            }
+                        }
+                        for (index, member) in draft.candidates.iter().enumerate() {
+                            candidates.spawn(text(
+                                format!("{} - {} ({})", index + 1, member.name, member.class),
+                                YELLOW.into(),
+                            ));
+                            candidates.spawn(text(member.stats.to_string(), Color::WHITE));
+                        }
+                    });
+            });
+            root.spawn(text(draft.message.clone(), Color::WHITE));
+            root.spawn(text(
+                "1-3 sign | UP/DOWN select | X release | ENTER line up".to_string(),
+                GREY.into(),
+            ));
+        });
+}
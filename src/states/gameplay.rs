@@ -1,13 +1,11 @@
-use bevy::{
-    color::palettes::css::{DARK_CYAN, GREEN, ORANGE},
-    prelude::*,
-    ui::widget::NodeImageMode,
-};
+use bevy::{color::palettes::css::GREEN, prelude::*, ui::widget::NodeImageMode};
 
 use crate::{
     AppSet, FontAsset, PanelBorderAsset,
-    actors::{self, actions::AbilitySlotMap, enemy::enemy_ai},
+    actors::{self, actions::AbilitySlotMap, enemy::enemy_ai, substitutions::Bench},
     map,
+    relics::Modifiers,
+    run::Run,
     theme::prelude::*,
 };
 
@@ -22,6 +20,7 @@ pub fn plugin(app: &mut App) {
             actions::setup_slotmap,
             map::spawn_field,
             startup,
+            show_conditions,
         ),
     )
     .add_systems(
@@ -56,64 +55,89 @@ pub fn plugin(app: &mut App) {
     );
 }
 
+#[derive(Component)]
+pub struct Header;
+
+/// Shows the relics our team brings to the match
+#[derive(Component)]
+pub struct RelicStrip;
+
 #[derive(Component)]
 pub struct InfoContainer;
 
+/// Banner that introduces the conditions of a match at kickoff
+#[derive(Component)]
+struct MatchIntro;
+
 #[derive(Component)]
 pub struct Log;
 
 fn startup(panel_border: Res<PanelBorderAsset>, mut commands: Commands) {
-    commands.ui_root().with_children(|root| {
-        root.spawn((
-            Name::from("Header"),
-            Node {
-                width: Val::Percent(100.0),
-                height: Val::Px(36.0),
-                padding: UiRect::all(Val::Percent(1.5)),
-                ..default()
-            },
-            ImageNode {
-                image: panel_border.image.clone_weak(),
-                image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
-                ..default()
-            },
-        ));
-        root.spawn((
-            Name::from("Info Container"),
-            Node {
-                width: Val::Px(344.0),
-                height: Val::Percent(100.0),
-                align_self: AlignSelf::FlexEnd,
-                flex_direction: FlexDirection::Column,
-                padding: UiRect::all(Val::Percent(1.5)),
-                ..default()
-            },
-            ImageNode {
-                image: panel_border.image.clone_weak(),
-                image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
-                ..default()
-            },
-            InfoContainer,
-        ));
-        root.spawn((
-            Name::from("Footer"),
-            Node {
-                width: Val::Percent(100.0),
-                height: Val::Px(180.0),
-                overflow: Overflow::clip_y(),
-                overflow_clip_margin: OverflowClipMargin::content_box().with_margin(8.0),
-                flex_direction: FlexDirection::Column,
-                padding: UiRect::all(Val::Percent(1.5)),
-                ..default()
-            },
-            ImageNode {
-                image: panel_border.image.clone_weak(),
-                image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
-                ..default()
-            },
-            Log,
-        ));
-    });
+    commands
+        .ui_root()
+        .insert(StateScoped(AppState::Gameplay))
+        .with_children(|root| {
+            root.spawn((
+                Name::from("Header"),
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(36.0),
+                    padding: UiRect::all(Val::Percent(1.5)),
+                    ..default()
+                },
+                ImageNode {
+                    image: panel_border.image.clone_weak(),
+                    image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
+                    ..default()
+                },
+                Header,
+            ));
+            root.spawn((
+                Name::from("Relic Strip"),
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(24.0),
+                    padding: UiRect::horizontal(Val::Percent(1.5)),
+                    ..default()
+                },
+                RelicStrip,
+            ));
+            root.spawn((
+                Name::from("Info Container"),
+                Node {
+                    width: Val::Px(344.0),
+                    height: Val::Percent(100.0),
+                    align_self: AlignSelf::FlexEnd,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Percent(1.5)),
+                    ..default()
+                },
+                ImageNode {
+                    image: panel_border.image.clone_weak(),
+                    image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
+                    ..default()
+                },
+                InfoContainer,
+            ));
+            root.spawn((
+                Name::from("Footer"),
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(180.0),
+                    overflow: Overflow::clip_y(),
+                    overflow_clip_margin: OverflowClipMargin::content_box().with_margin(8.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Percent(1.5)),
+                    ..default()
+                },
+                ImageNode {
+                    image: panel_border.image.clone_weak(),
+                    image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
+                    ..default()
+                },
+                Log,
+            ));
+        });
 }
 
 #[derive(Component)]
@@ -121,7 +145,9 @@ struct HasActed;
 
 fn designate_current_player(
     current_team: Res<State<GameplayStates>>,
-    mut query: Query<(Entity, &Team, &mut Stats, Option<&HasActed>)>,
+    modifiers: Res<Modifiers>,
+    mut sampler: ResMut<Sampler>,
+    mut query: Query<(Entity, &Team, &mut Stats, Option<&HasActed>), Without<Bench>>,
     mut commands: Commands,
 ) {
     let current = match current_team.get() {
@@ -129,25 +155,35 @@ fn designate_current_player(
         _ => panic!("designate_current_player can only be called in GameplayStates::Banner(...)"),
     };
 
-    let mut available_players: Vec<(Entity, &Stats)> = query
+    let mut available_players: Vec<(Entity, u8)> = query
         .iter()
         .filter(|(_, team, _, acted_option)| *team == current && acted_option.is_none())
-        .map(|(entity, _, stats, _)| (entity, stats))
+        .map(|(entity, team, stats, _)| {
+            (
+                entity,
+                modifiers.initiative(*team, stats.initiative, &mut sampler.0),
+            )
+        })
         .collect();
     if available_players.is_empty() {
-        for (entity, _, mut stats, _) in &mut query {
-            stats.reset_ap();
+        for (entity, team, mut stats, _) in &mut query {
+            stats.reset_ap(modifiers.bonus_ap(*team));
             commands.entity(entity).remove::<HasActed>();
         }
         available_players = query
             .iter()
             .filter(|(_, team, _, _)| *team == current)
-            .map(|(entity, _, stats, _)| (entity, stats))
+            .map(|(entity, team, stats, _)| {
+                (
+                    entity,
+                    modifiers.initiative(*team, stats.initiative, &mut sampler.0),
+                )
+            })
             .collect();
     }
     if let Some((selected, _)) = available_players
         .iter()
-        .max_by_key(|(_, stats)| stats.initiative)
+        .max_by_key(|(_, initiative)| *initiative)
     {
         commands.entity(*selected).insert(CurrentPlayer);
     }
@@ -157,16 +193,12 @@ fn remove_actions(mut ability_slot: Single<&mut AbilitySlotMap>) {
     ability_slot.clear();
 }
 
-fn paint_character(mut query: Query<(&mut Sprite, &Team, Option<&CurrentPlayer>)>) {
-    for (mut sprite, team, player_option) in &mut query {
-        let color = match player_option {
-            Some(_) => GREEN,
-            None => match team {
-                Team::Player => ORANGE.into(),
-                Team::Enemy => DARK_CYAN.into(),
-            },
+fn paint_character(mut query: Query<(&mut Sprite, &Kit, Option<&CurrentPlayer>)>) {
+    for (mut sprite, kit, player_option) in &mut query {
+        sprite.color = match player_option {
+            Some(_) => GREEN.into(),
+            None => kit.0,
         };
-        sprite.color = color.into();
     }
 }
 
@@ -187,7 +219,11 @@ fn show_banner(
 ) {
     commands
         .ui_root()
-        .insert((ZIndex(1), ImageNodeFadeInOut::default().with_t(0.6)))
+        .insert((
+            ZIndex(1),
+            ImageNodeFadeInOut::default().with_t(0.6),
+            StateScoped(AppState::Gameplay),
+        ))
         .with_children(|root| {
             root.spawn((
                 Node {
@@ -230,6 +266,71 @@ fn show_banner(
         });
 }
 
+/// Shows the conditions of the match below the banner of the first turn
+fn show_conditions(
+    run: Res<Run>,
+    font_asset: Res<FontAsset>,
+    panel_border: Res<PanelBorderAsset>,
+    mut commands: Commands,
+) {
+    let Some(opponent) = run
+        .opponent()
+        .filter(|opponent| !opponent.conditions.is_empty())
+    else {
+        return;
+    };
+    commands
+        .ui_root()
+        .insert((
+            ZIndex(1),
+            ImageNodeFadeInOut::default(),
+            MatchIntro,
+            StateScoped(AppState::Gameplay),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(60.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(20.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ImageNode {
+                    image: panel_border.image.clone_weak(),
+                    image_mode: NodeImageMode::Sliced(panel_border.slicer.clone()),
+                    ..default()
+                },
+            ))
+            .with_children(|banner| {
+                banner.spawn((
+                    Text::new("MATCH CONDITIONS"),
+                    TextFont {
+                        font: font_asset.clone_weak(),
+                        font_size: 30.0,
+                        ..default()
+                    },
+                ));
+                for condition in &opponent.conditions {
+                    banner.spawn((
+                        Text::new(format!(
+                            "{}: {}",
+                            condition.label().to_uppercase(),
+                            condition.description()
+                        )),
+                        TextFont {
+                            font: font_asset.clone_weak(),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+        });
+}
+
 fn fade(
     fades: Query<(&ImageNodeFadeInOut, &Children)>,
     mut images: Query<(&mut ImageNode, &Children)>,
@@ -252,12 +353,16 @@ fn fade(
 fn remove_banner(
     state: Res<State<GameplayStates>>,
     mut next: ResMut<NextState<GameplayStates>>,
-    fades: Query<(Entity, &ImageNodeFadeInOut)>,
+    fades: Query<(Entity, &ImageNodeFadeInOut, Has<MatchIntro>)>,
     mut commands: Commands,
 ) {
-    for (entity, fade) in &fades {
+    for (entity, fade, intro) in &fades {
         if fade.elapsed() {
             commands.entity(entity).despawn_recursive();
+            // the match intro fades out on its own time
+            if intro {
+                continue;
+            }
             let next_state = match state.get() {
                 GameplayStates::Banner(team) => match team {
                     &Team::Player => GameplayStates::PlayerTurn,
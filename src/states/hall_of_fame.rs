@@ -0,0 +1,57 @@
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{actors::formations::Formations, profile::Profile, run::Run, theme::prelude::*};
+
+use super::AppState;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(AppState::HallOfFame), spawn_hall_of_fame)
+        .add_systems(
+            Update,
+            new_run.run_if(input_just_pressed(KeyCode::Enter).and(in_state(AppState::HallOfFame))),
+        );
+}
+
+/// Players of a run that fit on a line
+const SQUAD_PREVIEW: usize = 4;
+
+fn spawn_hall_of_fame(profile: Res<Profile>, mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert((Name::new("Hall of Fame"), StateScoped(AppState::HallOfFame)))
+        .with_children(|root| {
+            root.label("HALL OF FAME");
+            for record in &profile.hall_of_fame {
+                let reached = if record.won {
+                    "CUP WINNERS".to_string()
+                } else {
+                    format!("OUT IN THE {}", record.reached)
+                };
+                let squad: Vec<_> = record.squad.iter().take(SQUAD_PREVIEW).cloned().collect();
+                root.label(format!("SEED {} - {}", record.seed, reached));
+                root.label(format!(
+                    "  {}{}",
+                    squad.join(", "),
+                    if record.squad.len() > SQUAD_PREVIEW {
+                        format!(" and {} more", record.squad.len() - SQUAD_PREVIEW)
+                    } else {
+                        String::new()
+                    }
+                ));
+            }
+            if profile.hall_of_fame.is_empty() {
+                root.label("No runs yet");
+            }
+            root.label("Press ENTER to start a new run");
+        });
+}
+
+fn new_run(
+    mut run: ResMut<Run>,
+    profile: Res<Profile>,
+    formations: Res<Formations>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    *run = Run::new(rand::random(), &profile, &formations);
+    next.set(AppState::PreMatch);
+}
@@ -25,7 +25,7 @@ fn spawn_loading_screen(mut commands: Commands) {
 }
 
 fn continue_to_title_screen(mut next_screen: ResMut<NextState<AppState>>) {
-    next_screen.set(AppState::Gameplay);
+    next_screen.set(AppState::PreMatch);
 }
 
 fn all_assets_loaded(resource_handles: Res<ResourceHandles>) -> bool {
@@ -2,9 +2,14 @@ use bevy::prelude::*;
 
 use crate::actors::Team;
 
+mod draft;
 pub mod gameplay;
+mod hall_of_fame;
 mod loading;
+mod pre_match;
+mod shop;
 mod splash;
+mod summary;
 
 #[derive(States, Debug, Hash, PartialEq, Eq, Clone, Default)]
 pub enum AppState {
@@ -14,6 +19,16 @@ pub enum AppState {
     Title,
     Credits,
     Gameplay,
+    /// Between two matches of a run and after the last one
+    Summary,
+    /// Buying relics before the next match
+    Shop,
+    /// Signing and releasing players before the next match
+    Draft,
+    /// Picking the formation and who starts where before kickoff
+    PreMatch,
+    /// Past runs of the profile
+    HallOfFame,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, SubStates)]
@@ -35,5 +50,14 @@ pub fn plugin(app: &mut App) {
         .add_sub_state::<GameplayStates>();
 
     app.enable_state_scoped_entities::<AppState>();
-    app.add_plugins((splash::plugin, loading::plugin, gameplay::plugin));
+    app.add_plugins((
+        splash::plugin,
+        loading::plugin,
+        gameplay::plugin,
+        summary::plugin,
+        shop::plugin,
+        draft::plugin,
+        pre_match::plugin,
+        hall_of_fame::plugin,
+    ));
 }
@@ -0,0 +1,251 @@
+use bevy::{
+    color::palettes::css::{GREY, YELLOW},
+    prelude::*,
+};
+
+use crate::{
+    AppSet, FontAsset, actors::formations::Formations, profile::Profile, run::Run,
+    theme::prelude::*,
+};
+
+use super::AppState;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Briefing>()
+        .add_systems(OnEnter(AppState::PreMatch), spawn_pre_match)
+        .add_systems(
+            Update,
+            (
+                handle_input.in_set(AppSet::RecordInput),
+                show_pre_match
+                    .in_set(AppSet::Update)
+                    .run_if(resource_changed::<Briefing>.or(resource_changed::<Run>)),
+            )
+                .run_if(in_state(AppState::PreMatch)),
+        );
+}
+
+/// What the last change to the lineup did
+#[derive(Resource, Default)]
+struct Briefing {
+    message: String,
+}
+
+#[derive(Component)]
+struct PreMatchScreen;
+
+/// An anchor of the formation that squad members can be dropped on
+#[derive(Component)]
+struct SlotEntry(usize);
+
+/// A squad member that can be dragged onto an anchor
+#[derive(Component)]
+struct RosterEntry(usize);
+
+fn spawn_pre_match(mut briefing: ResMut<Briefing>, mut commands: Commands) {
+    briefing.message = "Drag players onto the formation to pick who starts where".to_string();
+    commands.ui_root().insert((
+        Name::new("Pre-Match"),
+        StateScoped(AppState::PreMatch),
+        PreMatchScreen,
+    ));
+}
+
+/// LEFT and RIGHT switch the formation, L, P, W and C change the team instructions and ENTER kicks off
+fn handle_input(
+    input: Res<ButtonInput<KeyCode>>,
+    profile: Res<Profile>,
+    formations: Res<Formations>,
+    mut briefing: ResMut<Briefing>,
+    mut run: ResMut<Run>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    let step = if input.just_pressed(KeyCode::ArrowRight) {
+        1
+    } else if input.just_pressed(KeyCode::ArrowLeft) {
+        -1
+    } else {
+        0
+    };
+    if step != 0 {
+        let formations = profile.formations(&formations);
+        let current = formations
+            .iter()
+            .position(|formation| *formation == run.formation)
+            .unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(formations.len() as isize) as usize;
+        run.set_formation(formations[next].clone());
+        briefing.message = format!("The team lines up in a {}", run.formation);
+    }
+    let instructions = run.instructions;
+    if input.just_pressed(KeyCode::KeyL) {
+        run.instructions.line = instructions.line.next();
+    }
+    if input.just_pressed(KeyCode::KeyP) {
+        run.instructions.pressing = instructions.pressing.next();
+    }
+    if input.just_pressed(KeyCode::KeyW) {
+        run.instructions.width = instructions.width.next();
+    }
+    if input.just_pressed(KeyCode::KeyC) {
+        run.instructions.counter_attack = !instructions.counter_attack;
+    }
+    if input.just_pressed(KeyCode::Enter) {
+        next.set(AppState::Gameplay);
+    }
+}
+
+fn drop_on_slot(
+    trigger: Trigger<Pointer<DragDrop>>,
+    slots: Query<&SlotEntry>,
+    roster: Query<&RosterEntry>,
+    mut run: ResMut<Run>,
+    mut briefing: ResMut<Briefing>,
+) {
+    let (Ok(slot), Ok(member)) = (slots.get(trigger.entity()), roster.get(trigger.dropped)) else {
+        return;
+    };
+    let name = run.squad[member.0].name.clone();
+    briefing.message = if run.assign(member.0, slot.0) {
+        format!(
+            "{} starts as a {}",
+            name,
+            run.formation.anchors()[slot.0].class
+        )
+    } else {
+        format!("{} is sidelined for this match", name)
+    };
+}
+
+/// Clicking an anchor switches the role of the player starting on it
+fn switch_role(
+    trigger: Trigger<Pointer<Click>>,
+    slots: Query<&SlotEntry>,
+    mut run: ResMut<Run>,
+    mut briefing: ResMut<Briefing>,
+) {
+    let Some(index) = slots
+        .get(trigger.entity())
+        .ok()
+        .and_then(|slot| run.starter(slot.0))
+    else {
+        return;
+    };
+    let member = &mut run.squad[index];
+    member.role = member.role.next();
+    briefing.message = format!("{} plays a {} role", member.name, member.role);
+}
+
+fn show_pre_match(
+    run: Res<Run>,
+    briefing: Res<Briefing>,
+    font_asset: Res<FontAsset>,
+    screen: Single<Entity, With<PreMatchScreen>>,
+    mut commands: Commands,
+) {
+    let text = |text: String, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: font_asset.font.clone_weak(),
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+    let lineup = run.lineup();
+    let opponent = run
+        .opponent()
+        .map_or_else(String::new, |opponent| format!(" vs {}", opponent.name));
+    commands
+        .entity(*screen)
+        .despawn_descendants()
+        .with_children(|root| {
+            root.label(format!("PRE-MATCH - {}{}", run.formation, opponent));
+            root.spawn(Node {
+                column_gap: Val::Px(32.0),
+                ..default()
+            })
+            .with_children(|columns| {
+                columns
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    })
+                    .with_children(|slots| {
+                        for (index, anchor) in run.formation.anchors().iter().enumerate() {
+                            let starter = lineup
+                                .starters
+                                .iter()
+                                .find(|(slot, _)| *slot == index)
+                                .map_or_else(
+                                    || "-".to_string(),
+                                    |(_, member)| format!("{} [{}]", member.name, member.role),
+                                );
+                            slots
+                                .spawn((
+                                    text(
+                                        format!("{:>2} {}: {}", index + 1, anchor.class, starter),
+                                        YELLOW.into(),
+                                    ),
+                                    SlotEntry(index),
+                                ))
+                                .observe(drop_on_slot)
+                                .observe(switch_role);
+                        }
+                    });
+                columns
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    })
+                    .with_children(|roster| {
+                        for (index, member) in run.squad.iter().enumerate() {
+                            let starts = lineup
+                                .starters
+                                .iter()
+                                .any(|(_, starter)| std::ptr::eq(*starter, member));
+                            let color = if member.stats.is_sidelined() {
+                                GREY.into()
+                            } else if starts {
+                                Color::WHITE
+                            } else {
+                                YELLOW.into()
+                            };
+                            roster.spawn((
+                                text(
+                                    format!(
+                                        "{} ({}) LV {}",
+                                        member.name,
+                                        member.class,
+                                        member.stats.level()
+                                    ),
+                                    color,
+                                ),
+                                RosterEntry(index),
+                            ));
+                        }
+                    });
+            });
+            if let Some(opponent) = run
+                .opponent()
+                .filter(|opponent| !opponent.conditions.is_empty())
+            {
+                let conditions: Vec<_> = opponent
+                    .conditions
+                    .iter()
+                    .map(|condition| condition.to_string())
+                    .collect();
+                root.spawn(text(
+                    format!("CONDITIONS: {}", conditions.join(", ")),
+                    GREY.into(),
+                ));
+            }
+            root.spawn(text(run.instructions.to_string(), YELLOW.into()));
+            root.spawn(text(briefing.message.clone(), Color::WHITE));
+            root.spawn(text(
+                "LEFT/RIGHT formation | drag a player onto a position | click a position to switch the role\nL line | P pressing | W width | C counter-attack | ENTER kick off".to_string(),
+                GREY.into(),
+            ));
+        });
+}
@@ -0,0 +1,117 @@
+use bevy::{
+    color::palettes::css::{GOLD, GREY, YELLOW},
+    prelude::*,
+};
+
+use crate::{AppSet, FontAsset, profile::Profile, relics::Relic, run::Run, theme::prelude::*};
+
+use super::AppState;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Shop>()
+        .add_systems(OnEnter(AppState::Shop), open_shop)
+        .add_systems(
+            Update,
+            (
+                handle_input.in_set(AppSet::RecordInput),
+                show_shop
+                    .in_set(AppSet::Update)
+                    .run_if(resource_changed::<Shop>.or(resource_changed::<Run>)),
+            )
+                .run_if(in_state(AppState::Shop)),
+        );
+}
+
+/// Relics on offer before the next match
+#[derive(Resource, Default)]
+struct Shop {
+    offers: Vec<Relic>,
+    message: String,
+}
+
+#[derive(Component)]
+struct ShopScreen;
+
+fn open_shop(run: Res<Run>, profile: Res<Profile>, mut shop: ResMut<Shop>, mut commands: Commands) {
+    *shop = Shop {
+        offers: run.shop(&profile),
+        message: "Relics help the whole team for the rest of the run".to_string(),
+    };
+    commands
+        .ui_root()
+        .insert((Name::new("Shop"), StateScoped(AppState::Shop), ShopScreen));
+}
+
+/// 1-3 buys a relic and ENTER goes on to the draft
+fn handle_input(
+    input: Res<ButtonInput<KeyCode>>,
+    mut shop: ResMut<Shop>,
+    mut run: ResMut<Run>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    let picks = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+    let pick = picks
+        .iter()
+        .position(|key| input.just_pressed(*key))
+        .and_then(|pick| shop.offers.get(pick).copied().map(|relic| (pick, relic)));
+    if let Some((pick, relic)) = pick {
+        shop.message = if run.buy(relic) {
+            shop.offers.remove(pick);
+            format!("Bought the {}", relic)
+        } else {
+            format!("The {} costs {} coins", relic, relic.price())
+        };
+    }
+    if input.just_pressed(KeyCode::Enter) {
+        next.set(AppState::Draft);
+    }
+}
+
+fn show_shop(
+    run: Res<Run>,
+    shop: Res<Shop>,
+    font_asset: Res<FontAsset>,
+    screen: Single<Entity, With<ShopScreen>>,
+    mut commands: Commands,
+) {
+    let text = |text: String, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: font_asset.font.clone_weak(),
+                ..default()
+            },
+            TextColor(color),
+        )
+    };
+    commands
+        .entity(*screen)
+        .despawn_descendants()
+        .with_children(|root| {
+            root.label(format!("SHOP - {} coins", run.coins));
+            for (index, relic) in shop.offers.iter().enumerate() {
+                let color = if relic.price() <= run.coins {
+                    YELLOW
+                } else {
+                    GREY
+                };
+                root.spawn(text(
+                    format!("{} - {} ({} coins)", index + 1, relic, relic.price()),
+                    color.into(),
+                ));
+                root.spawn(text(relic.description().to_string(), Color::WHITE));
+            }
+            if shop.offers.is_empty() {
+                root.spawn(text("Sold out".to_string(), GREY.into()));
+            }
+            let owned: Vec<_> = run.relics.iter().map(Relic::label).collect();
+            if !owned.is_empty() {
+                root.spawn(text(format!("OWNED: {}", owned.join(" | ")), GOLD.into()));
+            }
+            root.spawn(text(shop.message.clone(), Color::WHITE));
+            root.spawn(text(
+                "1-3 buy | ENTER go to the draft".to_string(),
+                GREY.into(),
+            ));
+        });
+}
@@ -0,0 +1,85 @@
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    actors::formations::Formations,
+    profile::{Profile, record_run},
+    run::Run,
+    theme::prelude::*,
+};
+
+use super::AppState;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(AppState::Summary),
+        (record_run, spawn_summary.after(record_run)),
+    )
+    .add_systems(
+        Update,
+        (
+            continue_run.run_if(input_just_pressed(KeyCode::Enter)),
+            show_hall_of_fame.run_if(input_just_pressed(KeyCode::KeyH)),
+        )
+            .run_if(in_state(AppState::Summary)),
+    );
+}
+
+fn spawn_summary(run: Res<Run>, profile: Res<Profile>, mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert((Name::new("Run Summary"), StateScoped(AppState::Summary)))
+        .with_children(|root| {
+            let title = if run.is_won() {
+                "CUP WINNERS"
+            } else if run.is_over() {
+                "KNOCKED OUT"
+            } else {
+                "THROUGH TO THE NEXT ROUND"
+            };
+            root.label(title);
+            for (opponent, result) in run.ladder.iter().zip(&run.results) {
+                root.label(format!("{}: {}", opponent.round, result));
+            }
+            match run.opponent() {
+                Some(opponent) if !run.is_over() => {
+                    root.label(format!(
+                        "NEXT: {} against {}",
+                        opponent.round, opponent.name
+                    ));
+                    root.label(format!(
+                        "They play {} in a {}, watch out for {}",
+                        opponent.style, opponent.formation, opponent.star
+                    ))
+                    .insert(TextColor(opponent.color));
+                    root.label(format!("COINS: {}", run.coins));
+                    root.label("Press ENTER to go to the shop");
+                }
+                _ => {
+                    for unlock in &profile.fresh {
+                        root.label(format!("UNLOCKED {}", unlock));
+                    }
+                    root.label("Press ENTER to start a new run or H for the hall of fame");
+                }
+            }
+        });
+}
+
+fn continue_run(
+    mut run: ResMut<Run>,
+    profile: Res<Profile>,
+    formations: Res<Formations>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    if run.is_over() {
+        *run = Run::new(rand::random(), &profile, &formations);
+        next.set(AppState::PreMatch);
+    } else {
+        next.set(AppState::Shop);
+    }
+}
+
+fn show_hall_of_fame(run: Res<Run>, mut next: ResMut<NextState<AppState>>) {
+    if run.is_over() {
+        next.set(AppState::HallOfFame);
+    }
+}
@@ -1,4 +1,7 @@
-use bevy::{color::palettes::css::GREY, prelude::*};
+use bevy::{
+    color::palettes::css::{GREY, RED},
+    prelude::*,
+};
 
 use crate::{
     FontAsset, PostUpdateSet,
@@ -92,6 +95,17 @@ fn update_ui(
                             ));
                         }
                     }
+                    PossibleAction::Interception(entity, chance) => {
+                        let name = interactables.get(*entity).unwrap();
+                        info.spawn((
+                            Text::from(format!("{} - {:.0}% to intercept", name, chance * 100.0)),
+                            TextFont {
+                                font: font_asset.clone_weak(),
+                                ..default()
+                            },
+                            TextColor(RED.into()),
+                        ));
+                    }
                     PossibleAction::Command(key, command, available) => {
                         let color = if *available {
                             Color::WHITE